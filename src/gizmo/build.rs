@@ -1,13 +1,22 @@
 use cxx_qt_build::{CxxQtBuilder, QmlModule};
 
 fn main() {
-    CxxQtBuilder::new()
+    let builder = CxxQtBuilder::new()
         .qt_module("Quick")
         .qt_module("Quick3D")
         .qml_module(QmlModule {
             uri: "com.oliv.gizmo",
-            rust_files: &["src/gizmo.rs"],
-            qml_files: &["./qml/main.qml"],
+            rust_files: &[
+                "src/gizmo.rs",
+                "src/gizmo_geometry.rs",
+                "src/gizmo_style.rs",
+                "src/ground_grid.rs",
+                "src/marquee_selector.rs",
+                "src/measure_tool.rs",
+                "src/view_cube.rs",
+            ],
+            qml_files: &["./qml/main.qml", "./qml/GizmoOccludedModel.qml"],
+            qrc_files: &["./qml/occluded_handles.vert", "./qml/occluded_handles.frag"],
             ..Default::default()
         })
         .cc_builder(|cc| {
@@ -16,7 +25,34 @@ fn main() {
 
             println!("cargo:rerun-if-changed=./cpp/gizmo.h");
             println!("cargo:rerun-if-changed=./cpp/gizmo.cpp");
+            println!("cargo:rerun-if-changed=./cpp/gizmo_geometry.h");
+            println!("cargo:rerun-if-changed=./cpp/gizmo_style.h");
+            println!("cargo:rerun-if-changed=./cpp/view_cube.h");
+            println!("cargo:rerun-if-changed=./cpp/measure_tool.h");
+            println!("cargo:rerun-if-changed=./cpp/marquee_selector.h");
         })
         .qobject_header("./cpp/gizmo.h")
-        .build();
+        .qobject_header("./cpp/gizmo_geometry.h")
+        .qobject_header("./cpp/gizmo_style.h")
+        .qobject_header("./cpp/view_cube.h")
+        .qobject_header("./cpp/measure_tool.h")
+        .qobject_header("./cpp/marquee_selector.h");
+
+    // Input scenarios of the tests, see interaction_tests.rs. They need QtTest, which the example
+    // doesn't ship.
+    let builder = if std::env::var_os("CARGO_FEATURE_INTERACTION_TESTS").is_some() {
+        builder
+            .qt_module("Test")
+            .cc_builder(|cc| {
+                cc.file("./cpp/gizmo_tests.cpp");
+
+                println!("cargo:rerun-if-changed=./cpp/gizmo_tests.h");
+                println!("cargo:rerun-if-changed=./cpp/gizmo_tests.cpp");
+            })
+            .file("src/interaction_tests.rs")
+    } else {
+        builder
+    };
+
+    builder.build();
 }
@@ -1,19 +1,31 @@
 // SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
 // SPDX-License-Identifier: MIT
 
+#include <QtCore/QMetaMethod>
+#include <QtCore/QMetaProperty>
+#include <QtGui/QFont>
+#include <QtGui/QMatrix4x4>
+#include <QtGui/QQuaternion>
+#include <QtGui/QTextLayout>
 #include <QtGui/QVector3D>
 #include <QtGui/QVector4D>
 #include <QtQuick/QQuickItem>
+#include <QtQuick/QQuickWindow>
 #include <QtQuick/QSGGeometryNode>
 #include <QtQuick/QSGGeometry>
+#include <QtQuick/QSGRectangleNode>
+#include <QtQuick/QSGTextNode>
 #include <QtQuick/QSGVertexColorMaterial>
 #include <QtQuick/QSGNode>
+#include <algorithm>
+#include <limits>
 
 #include "gizmo.h"
+#include "gizmo/src/gizmo_style.cxxqt.h"
 
 QSGNode *gizmo_update_paint_node(QSGNode *oldNode,
                                  rust::Slice<std::array<float, 2> const> vertices,
-                                 rust::Slice<std::array<float, 4> const> colors,
+                                 rust::Slice<std::array<std::uint8_t, 4> const> colors,
                                  rust::Slice<std::uint32_t const> indices)
 {
     assert(vertices.size() == colors.size());
@@ -44,7 +56,9 @@ QSGNode *gizmo_update_paint_node(QSGNode *oldNode,
     {
         node = static_cast<QSGGeometryNode *>(oldNode);
         geometry = node->geometry();
-        geometry->allocate(vertices.size(), indices.size());
+        // The buffers are overwritten in place while the handles keep the same shape
+        if (std::size_t(geometry->vertexCount()) != vertices.size() || std::size_t(geometry->indexCount()) != indices.size())
+            geometry->allocate(vertices.size(), indices.size());
     }
 
     assert(geometry != nullptr);
@@ -55,22 +69,11 @@ QSGNode *gizmo_update_paint_node(QSGNode *oldNode,
     {
         const auto &v = vertices[i];
         const auto &c = colors[i];
-        const auto x = v[0];
-        const auto y = v[1];
-        const auto red = static_cast<uchar>(c[0] * 255.f);
-        const auto green = static_cast<uchar>(c[1] * 255.f);
-        const auto blue = static_cast<uchar>(c[2] * 255.f);
-        const auto alpha = static_cast<uchar>(c[3] * 255.f);
-
-        vertex_data[i].set(x, y, red, green, blue, alpha);
+        vertex_data[i].set(v[0], v[1], c[0], c[1], c[2], c[3]);
     }
 
-    uint *index_data = geometry->indexDataAsUInt();
-    for (std::size_t i = 0; i < indices.size(); ++i)
-    {
-        index_data[i] = indices[i];
-        assert(index_data[i] < vertices.size());
-    }
+    assert(std::all_of(indices.begin(), indices.end(), [&](std::uint32_t index) { return index < vertices.size(); }));
+    std::copy(indices.begin(), indices.end(), geometry->indexDataAsUInt());
 
     node->markDirty(QSGNode::DirtyGeometry);
     return node;
@@ -81,12 +84,105 @@ std::size_t extract_target_count_from_qvariant(const QVariant &targets)
     return targetsList.size();
 }
 
-void extract_targets_from_qvariant(const QVariant &targets, rust::Slice<QVector3D> positions, rust::Slice<QVector4D> rotations, rust::Slice<QVector3D> scales)
+/// Whether `value` is a list of numbers rather than a `QVector3D` or `QVector4D`
+static bool isNumberList(const QVariant &value)
+{
+    return value.metaType() == QMetaType::fromType<QVariantList>();
+}
+
+/// Components of a vector given either as a `QVector3D`/`QVector4D` or, to keep double precision,
+/// as a list of numbers. `fallback` when it is neither.
+template<typename Vector, std::size_t N>
+static std::array<double, N> toDoubles(const QVariant &value, const std::array<double, N> &fallback)
+{
+    if (isNumberList(value))
+    {
+        const QVariantList list = value.toList();
+        if (std::size_t(list.size()) != N)
+            return fallback;
+
+        std::array<double, N> components;
+        for (std::size_t i = 0; i < N; ++i)
+            components[i] = list.at(int(i)).toDouble();
+        return components;
+    }
+
+    if (!value.canConvert<Vector>())
+        return fallback;
+
+    const Vector vector = qvariant_cast<Vector>(value);
+    std::array<double, N> components;
+    for (std::size_t i = 0; i < N; ++i)
+        components[i] = vector[int(i)];
+    return components;
+}
+
+/// `components` in the same representation as `original`: a list of numbers when the target gave
+/// one, a `QVector3D`/`QVector4D` otherwise
+template<typename Vector, std::size_t N>
+static QVariant fromDoubles(const QVariant &original, const std::array<double, N> &components)
+{
+    if (isNumberList(original))
+    {
+        QVariantList list;
+        list.reserve(int(N));
+        for (double component : components)
+            list.append(component);
+        return list;
+    }
+
+    Vector vector;
+    for (std::size_t i = 0; i < N; ++i)
+        vector[int(i)] = float(components[i]);
+    return vector;
+}
+
+/// Rotation of a target as the x, y, z, w components of a quaternion or, for a `QVector3D` or a
+/// list of 3 numbers, as Euler angles in the first three components with `euler` set
+static std::array<double, 4> toRotation(const QVariant &value, bool &euler)
+{
+    euler = value.metaType() == QMetaType::fromType<QVector3D>() || (isNumberList(value) && value.toList().size() == 3);
+    if (euler)
+    {
+        const auto angles = toDoubles<QVector3D, 3>(value, {0., 0., 0.});
+        return {angles[0], angles[1], angles[2], 0.};
+    }
+
+    if (value.metaType() == QMetaType::fromType<QQuaternion>())
+    {
+        const QQuaternion rotation = qvariant_cast<QQuaternion>(value);
+        return {rotation.x(), rotation.y(), rotation.z(), rotation.scalar()};
+    }
+
+    return toDoubles<QVector4D, 4>(value, {0., 0., 0., 1.});
+}
+
+/// `rotation` as Euler angles when `euler` is set, as a quaternion otherwise, in the same
+/// representation as `original`
+static QVariant fromRotation(const QVariant &original, const std::array<double, 4> &rotation, bool euler)
+{
+    if (euler)
+        return fromDoubles<QVector3D>(original, std::array<double, 3>{rotation[0], rotation[1], rotation[2]});
+
+    if (isNumberList(original))
+        return fromDoubles<QVector4D>(original, rotation);
+
+    // Narrowed to float, only array rotations keep double precision
+    return QQuaternion(float(rotation[3]), float(rotation[0]), float(rotation[1]), float(rotation[2]));
+}
+
+void extract_dvec3_from_qvariant(const QVariant &value, std::array<double, 3> &vector)
+{
+    vector = toDoubles<QVector3D, 3>(value, vector);
+}
+
+void extract_targets_from_qvariant(const QVariant &targets, rust::Slice<std::array<double, 3>> positions, rust::Slice<std::array<double, 4>> rotations, rust::Slice<bool> eulers, rust::Slice<std::array<double, 3>> scales)
 {
     const auto targetsList = targets.toList();
 
     assert(std::size_t(targetsList.size()) == positions.size());
     assert(std::size_t(targetsList.size()) == rotations.size());
+    assert(std::size_t(targetsList.size()) == eulers.size());
     assert(std::size_t(targetsList.size()) == scales.size());
 
     for (int i = 0; i < targetsList.size(); ++i)
@@ -94,32 +190,324 @@ void extract_targets_from_qvariant(const QVariant &targets, rust::Slice<QVector3
         const auto target = targetsList.at(i);
 
         const QMap<QString, QVariant> targetMap = target.toMap();
-        const QVariant position = targetMap.value("position", QVector3D());
-        const QVariant rotation = targetMap.value("rotation", QVector4D(0.f, 0.f, 0.f, 1.f));
-        const QVariant scale = targetMap.value("scale", QVector3D(1.f, 1.f, 1.f));
+        positions[i] = toDoubles<QVector3D, 3>(targetMap.value("position"), {0., 0., 0.});
+        rotations[i] = toRotation(targetMap.value("rotation"), eulers[i]);
+        scales[i] = toDoubles<QVector3D, 3>(targetMap.value("scale"), {1., 1., 1.});
+    }
+}
+
+void extract_target_parents_from_qvariant(const QVariant &targets, rust::Slice<std::array<double, 16>> parents)
+{
+    const auto targetsList = targets.toList();
+
+    assert(std::size_t(targetsList.size()) == parents.size());
 
-        positions[i] = qvariant_cast<QVector3D>(position);
-        rotations[i] = qvariant_cast<QVector4D>(rotation);
-        scales[i] = qvariant_cast<QVector3D>(scale);
+    for (int i = 0; i < targetsList.size(); ++i)
+    {
+        const QMap<QString, QVariant> targetMap = targetsList.at(i).toMap();
+        const QVariant parent = targetMap.value("parentTransform", QMatrix4x4());
+
+        if (isNumberList(parent) && parent.toList().size() == 16)
+        {
+            // Row-major like the arguments of Qt.matrix4x4, the slice is column-major
+            const QVariantList values = parent.toList();
+            for (int row = 0; row < 4; ++row)
+            {
+                for (int column = 0; column < 4; ++column)
+                    parents[i][std::size_t(column * 4 + row)] = values.at(row * 4 + column).toDouble();
+            }
+        }
+        else
+        {
+            const QMatrix4x4 matrix = qvariant_cast<QMatrix4x4>(parent);
+            std::copy_n(matrix.constData(), 16, parents[i].begin());
+        }
     }
 }
 
-QVariant transforms_to_qvariant(rust::Slice<QVector3D const> positions, rust::Slice<QVector4D const> rotations, rust::Slice<QVector3D const> scales)
+void extract_target_constraints_from_qvariant(const QVariant &targets, rust::Slice<std::array<bool, 3>> lockedTranslations, rust::Slice<std::array<bool, 3>> allowedRotations, rust::Slice<std::array<double, 3>> minPositions, rust::Slice<std::array<double, 3>> maxPositions, rust::Slice<QVector3D> minScales, rust::Slice<QVector3D> maxScales)
+{
+    const auto targetsList = targets.toList();
+
+    assert(std::size_t(targetsList.size()) == lockedTranslations.size());
+    assert(std::size_t(targetsList.size()) == allowedRotations.size());
+    assert(std::size_t(targetsList.size()) == minPositions.size());
+    assert(std::size_t(targetsList.size()) == maxPositions.size());
+    assert(std::size_t(targetsList.size()) == minScales.size());
+    assert(std::size_t(targetsList.size()) == maxScales.size());
+
+    constexpr float max = std::numeric_limits<float>::max();
+    const QVector3D lowest(-max, -max, -max);
+    const QVector3D highest(max, max, max);
+    constexpr double infinity = std::numeric_limits<double>::infinity();
+
+    for (int i = 0; i < targetsList.size(); ++i)
+    {
+        const QMap<QString, QVariant> targetMap = targetsList.at(i).toMap();
+
+        lockedTranslations[i] = {targetMap.value("lockTranslationX", false).toBool(),
+                                 targetMap.value("lockTranslationY", false).toBool(),
+                                 targetMap.value("lockTranslationZ", false).toBool()};
+        allowedRotations[i] = {targetMap.value("allowRotationX", true).toBool(),
+                               targetMap.value("allowRotationY", true).toBool(),
+                               targetMap.value("allowRotationZ", true).toBool()};
+        minPositions[i] = toDoubles<QVector3D, 3>(targetMap.value("minPosition"), {-infinity, -infinity, -infinity});
+        maxPositions[i] = toDoubles<QVector3D, 3>(targetMap.value("maxPosition"), {infinity, infinity, infinity});
+        minScales[i] = qvariant_cast<QVector3D>(targetMap.value("minScale", lowest));
+        maxScales[i] = qvariant_cast<QVector3D>(targetMap.value("maxScale", highest));
+    }
+}
+
+QVariant transforms_to_qvariant(const QVariant &targets, rust::Slice<std::array<double, 3> const> positions, rust::Slice<std::array<double, 4> const> rotations, bool euler, rust::Slice<std::array<double, 3> const> scales)
 {
     assert(positions.size() == rotations.size());
     assert(positions.size() == scales.size());
 
+    const auto targetsList = targets.toList();
+
     QVector<QVariant> transforms;
     for (std::size_t i = 0; i < positions.size(); ++i)
     {
         QMap<QString, QVariant> transform;
+        if (i < std::size_t(targetsList.size()))
+            transform = targetsList.at(i).toMap();
 
-        transform.insert("position", positions[i]);
-        transform.insert("rotation", rotations[i]);
-        transform.insert("scale", scales[i]);
+        transform.insert("position", fromDoubles<QVector3D>(transform.value("position"), positions[i]));
+        transform.insert("rotation", fromRotation(transform.value("rotation"), rotations[i], euler));
+        transform.insert("scale", fromDoubles<QVector3D>(transform.value("scale"), scales[i]));
 
         transforms.emplace_back(transform);
     }
 
     return transforms;
 }
+
+/// Read the property `name` of a gadget stored in `gadget`, such as `QQuick3DBounds3`
+static QVariant gadgetProperty(const QVariant &gadget, const char *name)
+{
+    const QMetaObject *metaObject = gadget.metaType().metaObject();
+    if (!metaObject)
+        return {};
+
+    const int index = metaObject->indexOfProperty(name);
+    if (index < 0)
+        return {};
+
+    return metaObject->property(index).readOnGadget(gadget.constData());
+}
+
+/// World positions a snap target can be snapped to, the scene being placed at `sceneOrigin`.
+///
+/// A snap target is either a world position, given as a `QVector3D` or a list of numbers, or a
+/// QtQuick3D node snapping by its origin and, for models, the corners of its bounding box. Nodes
+/// are only part of QtQuick3D private API, so they are read through their properties.
+static QList<std::array<double, 3>> snapPoints(const QVariant &snapTarget, const std::array<double, 3> &sceneOrigin)
+{
+    if (snapTarget.metaType() == QMetaType::fromType<QVector3D>() || (isNumberList(snapTarget) && snapTarget.toList().size() == 3))
+        return {toDoubles<QVector3D, 3>(snapTarget, {0., 0., 0.})};
+
+    const QObject *node = snapTarget.value<QObject *>();
+    if (!node)
+        return {};
+
+    // Scene positions are single precision and relative to the scene origin
+    const auto toWorld = [&](const QVector3D &point) {
+        return std::array<double, 3>{sceneOrigin[0] + point.x(), sceneOrigin[1] + point.y(), sceneOrigin[2] + point.z()};
+    };
+    QList<std::array<double, 3>> points{toWorld(node->property("scenePosition").value<QVector3D>())};
+
+    const QVariant bounds = node->property("bounds");
+    if (bounds.isValid())
+    {
+        const QVector3D minimum = gadgetProperty(bounds, "minimum").value<QVector3D>();
+        const QVector3D maximum = gadgetProperty(bounds, "maximum").value<QVector3D>();
+        const QMatrix4x4 sceneTransform = node->property("sceneTransform").value<QMatrix4x4>();
+
+        if (minimum != maximum)
+        {
+            for (int corner = 0; corner < 8; ++corner)
+            {
+                const QVector3D local((corner & 1) ? maximum.x() : minimum.x(),
+                                      (corner & 2) ? maximum.y() : minimum.y(),
+                                      (corner & 4) ? maximum.z() : minimum.z());
+                points.push_back(toWorld(sceneTransform.map(local)));
+            }
+        }
+    }
+
+    return points;
+}
+
+std::size_t extract_snap_point_count_from_qvariant(const QVariant &snapTargets)
+{
+    std::size_t count = 0;
+    for (const QVariant &snapTarget : snapTargets.toList())
+        count += snapPoints(snapTarget, {0., 0., 0.}).size();
+
+    return count;
+}
+
+void extract_snap_points_from_qvariant(const QVariant &snapTargets, const std::array<double, 3> &sceneOrigin, rust::Slice<std::array<double, 3>> points, rust::Slice<std::size_t> sources)
+{
+    assert(points.size() == sources.size());
+
+    const auto snapTargetsList = snapTargets.toList();
+
+    std::size_t i = 0;
+    for (int source = 0; source < snapTargetsList.size(); ++source)
+    {
+        for (const std::array<double, 3> &point : snapPoints(snapTargetsList.at(source), sceneOrigin))
+        {
+            assert(i < points.size());
+            points[i] = point;
+            sources[i] = source;
+            ++i;
+        }
+    }
+}
+
+QVariant qvariant_list_at(const QVariant &list, std::size_t index)
+{
+    const auto variantList = list.toList();
+    if (index >= std::size_t(variantList.size()))
+        return {};
+
+    return variantList.at(index);
+}
+
+QVariant qvariant_list_select(const QVariant &list, rust::Slice<std::size_t const> indices)
+{
+    const auto variantList = list.toList();
+
+    QVariantList selected;
+    for (const std::size_t index : indices)
+    {
+        assert(index < std::size_t(variantList.size()));
+        selected.push_back(variantList.at(index));
+    }
+
+    return selected;
+}
+
+bool qvariant_list_contains(const QVariant &list, const QVariant &item)
+{
+    return list.toList().contains(item);
+}
+
+QVariant qvariant_list_concat(const QVariant &first, const QVariant &second)
+{
+    return first.toList() + second.toList();
+}
+
+const GizmoStyle &gizmo_style_from_base(const GizmoStyleBase &style)
+{
+    return static_cast<const GizmoStyle &>(style);
+}
+
+bool pick_scene_position(QQuickItem &view, QPointF position, QVector3D &scenePosition)
+{
+    // View3D is only part of QtQuick3D private API, its pick result is read as a gadget
+    const QMetaObject *metaObject = view.metaObject();
+    const int index = metaObject->indexOfMethod("pick(float,float)");
+    if (index < 0)
+        return false;
+
+    const QMetaMethod method = metaObject->method(index);
+    QVariant result(method.returnMetaType());
+    const bool invoked = method.invoke(&view, Qt::DirectConnection,
+                                       QGenericReturnArgument(method.typeName(), result.data()),
+                                       Q_ARG(float, float(position.x())), Q_ARG(float, float(position.y())));
+    if (!invoked || !gadgetProperty(result, "objectHit").value<QObject *>())
+        return false;
+
+    scenePosition = gadgetProperty(result, "scenePosition").value<QVector3D>();
+    return true;
+}
+
+namespace {
+/// Label of `update_label_nodes`, text on a dark background moved by its transform. `QSGTextNode`
+/// needs Qt 6.7.
+class LabelNode : public QSGTransformNode
+{
+public:
+    explicit LabelNode(QQuickWindow &window)
+        : _background(window.createRectangleNode())
+        , _text(window.createTextNode())
+    {
+        _background->setColor(QColor(0, 0, 0, 160));
+        _text->setColor(Qt::white);
+        appendChildNode(_background);
+        appendChildNode(_text);
+    }
+
+    /// Lay out `text` again when it changed. The text node draws its glyphs from the glyph cache
+    /// of the window, so a new value while dragging doesn't create any texture.
+    void setText(const QString &text)
+    {
+        if (_laidOut && text == _shownText)
+            return;
+
+        constexpr qreal padding = 3.;
+        QFont font;
+        font.setPixelSize(12);
+        QTextLayout layout(text, font);
+        layout.beginLayout();
+        const QTextLine line = layout.createLine();
+        layout.endLayout();
+
+        _size = QSizeF(line.naturalTextWidth(), line.height()) + QSizeF(2. * padding, 2. * padding);
+        _background->setRect(QRectF(QPointF(), _size));
+        _text->clear();
+        _text->addTextLayout(QPointF(padding, padding), &layout);
+        _shownText = text;
+        _laidOut = true;
+    }
+
+    void setCenter(QPointF center)
+    {
+        QMatrix4x4 matrix;
+        matrix.translate(float(center.x() - _size.width() / 2.), float(center.y() - _size.height() / 2.));
+        setMatrix(matrix);
+    }
+
+private:
+    QSGRectangleNode *_background;
+    QSGTextNode *_text;
+    QString _shownText;
+    QSizeF _size;
+    bool _laidOut = false;
+};
+} // namespace
+
+void update_label_nodes(QQuickItem &item, QSGNode &node, rust::Slice<QPointF const> positions, rust::Slice<QString const> texts)
+{
+    assert(positions.size() == texts.size());
+
+    // Labels are reused in order, only the ones showing a new text are laid out again
+    QSGNode *child = node.firstChild();
+    QQuickWindow *window = item.window();
+    for (std::size_t i = 0; i < texts.size() && window; ++i)
+    {
+        LabelNode *label = static_cast<LabelNode *>(child);
+        if (label)
+        {
+            child = child->nextSibling();
+        }
+        else
+        {
+            label = new LabelNode(*window);
+            node.appendChildNode(label);
+        }
+
+        label->setText(texts[i]);
+        label->setCenter(positions[i]);
+    }
+
+    while (child)
+    {
+        QSGNode *next = child->nextSibling();
+        node.removeChildNode(child);
+        delete child;
+        child = next;
+    }
+}
@@ -3,33 +3,305 @@
 
 #pragma once
 
+#include <QtCore/QMetaMethod>
+#include <QtCore/QMetaProperty>
+#include <QtCore/QPointer>
 #include <QtCore/QVariant>
+#include <QtCore/QtMath>
+#include <QtGui/QCursor>
+#include <QtGui/QEventPoint>
+#include <QtGui/QKeyEvent>
+#include <QtGui/QPointingDevice>
+#include <QtGui/QTabletEvent>
+#include <QtGui/QTouchEvent>
+#include <QtGui/QTransform>
 #include <QtQuick/QSGNode>
 #include <QtQuick/QQuickItem>
+#include <QtQuick/QQuickWindow>
+#include <algorithm>
 #include <array>
 #include <cstdint>
 
+#include "gizmo_style.h"
 #include "rust/cxx.h"
 
+/// Replace the children of `node` by labels of `item` showing `texts` centered on `positions`, as
+/// scene graph text reusing the label nodes already there
+void update_label_nodes(QQuickItem &item, QSGNode &node, rust::Slice<QPointF const> positions, rust::Slice<QString const> texts);
+
 class GizmoInteractionItem : public QQuickItem
 {
     Q_OBJECT
 
+    /// Radius in logical pixels around a touch point in which gizmo handles can be grabbed
+    Q_PROPERTY(qreal touchPickRadius READ touchPickRadius WRITE setTouchPickRadius NOTIFY touchPickRadiusChanged)
+    /// View3D the camera renders to, when the gizmo doesn't exactly cover it
+    Q_PROPERTY(QQuickItem *view READ view WRITE setView NOTIFY viewChanged)
+    /// Area of the item the camera renders to, used when `view` isn't set
+    Q_PROPERTY(QRectF viewport READ viewport WRITE setViewport NOTIFY viewportChanged)
+    /// Device pixel ratio of the screen showing the item
+    Q_PROPERTY(qreal devicePixelRatio READ devicePixelRatio NOTIFY devicePixelRatioChanged)
+    /// Shared visuals replacing the colors, alphas, stroke width and size of the gizmo when set
+    Q_PROPERTY(GizmoStyleBase *style READ style WRITE setStyle NOTIFY styleChanged)
+
 public:
+    /// Size in bytes of a vertex of the 3D geometry: position then RGBA color
+    static constexpr int GeometryVertexStride = 7 * sizeof(float);
+
     GizmoInteractionItem(QQuickItem *parent = nullptr)
         : QQuickItem(parent)
     {
         setAcceptHoverEvents(true);
         setAcceptedMouseButtons(Qt::LeftButton);
+        setAcceptTouchEvents(true);
+        setActiveFocusOnTab(true);
     }
     virtual ~GizmoInteractionItem() = default;
 
+    qreal touchPickRadius() const
+    {
+        return _touchPickRadius;
+    }
+
+    void setTouchPickRadius(qreal radius)
+    {
+        if (qFuzzyCompare(_touchPickRadius, radius))
+            return;
+
+        _touchPickRadius = radius;
+        Q_EMIT touchPickRadiusChanged();
+    }
+
+    qreal devicePixelRatio() const
+    {
+        return _devicePixelRatio;
+    }
+
+    /// Last known position of the mouse cursor, in view coordinates
+    QPointF cursorPosition() const
+    {
+        return mapToView(_cursorPosition);
+    }
+
+    QQuickItem *view() const
+    {
+        return _view;
+    }
+
+    void setView(QQuickItem *view)
+    {
+        if (_view == view)
+            return;
+
+        if (_view)
+            disconnect(_view, nullptr, this, nullptr);
+
+        _view = view;
+        if (_view)
+        {
+            connect(_view, &QQuickItem::xChanged, this, &QQuickItem::update);
+            connect(_view, &QQuickItem::yChanged, this, &QQuickItem::update);
+            connect(_view, &QQuickItem::widthChanged, this, &QQuickItem::update);
+            connect(_view, &QQuickItem::heightChanged, this, &QQuickItem::update);
+            connect(_view, &QQuickItem::rotationChanged, this, &QQuickItem::update);
+            connect(_view, &QQuickItem::scaleChanged, this, &QQuickItem::update);
+        }
+
+        Q_EMIT viewChanged();
+        update();
+    }
+
+    GizmoStyleBase *style() const
+    {
+        return _style;
+    }
+
+    void setStyle(GizmoStyleBase *style)
+    {
+        if (_style == style)
+            return;
+
+        if (_style)
+            disconnect(_style, nullptr, this, nullptr);
+
+        _style = style;
+        if (_style)
+        {
+            // Repaint on any change of the style, whatever property it is
+            const QMetaMethod updateSlot = staticMetaObject.method(staticMetaObject.indexOfSlot("update()"));
+            const QMetaObject *metaObject = _style->metaObject();
+            for (int i = GizmoStyleBase::staticMetaObject.propertyOffset(); i < metaObject->propertyCount(); ++i)
+            {
+                const QMetaProperty property = metaObject->property(i);
+                if (property.hasNotifySignal())
+                    connect(_style, property.notifySignal(), this, updateSlot);
+            }
+            // Fall back to the gizmo own visuals
+            connect(_style, &QObject::destroyed, this, [this] {
+                Q_EMIT styleChanged();
+                update();
+            });
+        }
+
+        Q_EMIT styleChanged();
+        update();
+    }
+
+    QRectF viewport() const
+    {
+        return _viewport;
+    }
+
+    void setViewport(const QRectF &viewport)
+    {
+        if (_viewport == viewport)
+            return;
+
+        _viewport = viewport;
+        Q_EMIT viewportChanged();
+        update();
+    }
+
+    /// Size of the area the camera renders to
+    QSizeF viewSize() const
+    {
+        if (_view)
+            return _view->size();
+        if (_viewport.isValid())
+            return _viewport.size();
+        return size();
+    }
+
+    /// Map `position` from the item to the view
+    QPointF mapToView(QPointF position) const
+    {
+        if (_view)
+            return mapToItem(_view, position);
+        if (_viewport.isValid())
+            return position - _viewport.topLeft();
+        return position;
+    }
+
+    /// Fill `matrix` with the affine transform from view to item coordinates, as the `m11`,
+    /// `m12`, `m21`, `m22`, `dx` and `dy` components of a QTransform
+    void viewToItemTransform(rust::Slice<float> matrix) const
+    {
+        assert(matrix.size() == 6);
+
+        QTransform transform;
+        if (_view)
+            transform = _view->itemTransform(const_cast<GizmoInteractionItem *>(this), nullptr);
+        else if (_viewport.isValid())
+            transform = QTransform::fromTranslate(_viewport.x(), _viewport.y());
+
+        const std::array<qreal, 6> components{
+            transform.m11(), transform.m12(), transform.m21(), transform.m22(), transform.dx(), transform.dy()};
+        std::copy(components.begin(), components.end(), matrix.begin());
+    }
+
+    /// While in modal interaction, cursor moves drag the gizmo without any button pressed
+    void setModalInteraction(bool modal)
+    {
+        _modal = modal;
+        _hovering = false;
+    }
+
+    void setCursorShape(int shape)
+    {
+        setCursor(QCursor(static_cast<Qt::CursorShape>(shape)));
+    }
+
+    void resetCursorShape()
+    {
+        unsetCursor();
+    }
+
+    /// Publish the handles as 3D triangles, rendered by GizmoGeometry
+    void setGeometryData(rust::Slice<std::array<float, 3> const> vertices,
+                         rust::Slice<std::array<float, 4> const> colors,
+                         rust::Slice<std::uint32_t const> indices)
+    {
+        assert(vertices.size() == colors.size());
+
+        QByteArray vertexData(qsizetype(vertices.size()) * GeometryVertexStride, Qt::Uninitialized);
+        float *vertex = reinterpret_cast<float *>(vertexData.data());
+        for (std::size_t i = 0; i < vertices.size(); ++i)
+        {
+            vertex = std::copy(vertices[i].begin(), vertices[i].end(), vertex);
+            vertex = std::copy(colors[i].begin(), colors[i].end(), vertex);
+        }
+
+        const QByteArray indexData(reinterpret_cast<const char *>(indices.data()),
+                                   qsizetype(indices.size() * sizeof(std::uint32_t)));
+        // Spare GizmoGeometry a rebuild when the handles didn't move
+        if (vertexData == _geometryVertexData && indexData == _geometryIndexData)
+            return;
+
+        _geometryVertexData = vertexData;
+        _geometryIndexData = indexData;
+        Q_EMIT geometryDataChanged();
+    }
+
+    QByteArray geometryVertexData() const
+    {
+        return _geometryVertexData;
+    }
+
+    QByteArray geometryIndexData() const
+    {
+        return _geometryIndexData;
+    }
+
+    /// Draw `texts` centered on `positions` as children of `node`
+    void updateLabels(QSGNode *node, rust::Slice<QPointF const> positions, rust::Slice<QString const> texts)
+    {
+        if (node)
+            update_label_nodes(*this, *node, positions, texts);
+    }
+
+Q_SIGNALS:
+    void touchPickRadiusChanged();
+    void viewChanged();
+    void viewportChanged();
+    void geometryDataChanged();
+    void devicePixelRatioChanged();
+    void styleChanged();
+
 protected:
+    void itemChange(ItemChange change, const ItemChangeData &value) override
+    {
+        QQuickItem::itemChange(change, value);
+
+        if (change == ItemSceneChange)
+        {
+            // The window may move to a screen with another ratio without the item noticing
+            disconnect(_screenConnection);
+            disconnect(_frameConnection);
+            if (value.window)
+            {
+                _screenConnection = connect(value.window, &QWindow::screenChanged, this,
+                                            &GizmoInteractionItem::updateDevicePixelRatio);
+                // Emitted on the GUI thread before the scene is synchronized, so a GizmoGeometry
+                // shows the handles of the frame being rendered
+                _frameConnection = connect(value.window, &QQuickWindow::afterAnimating, this,
+                                           &GizmoInteractionItem::publishGeometryData);
+            }
+        }
+        if (change == ItemSceneChange || change == ItemDevicePixelRatioHasChanged)
+            updateDevicePixelRatio();
+    }
+
     void hoverEnterEvent(QHoverEvent *event) override
     {
         assert(_hovering == false);
 
-        if (pickPreview(event->position()))
+        _cursorPosition = event->position();
+        if (_modal)
+        {
+            event->accept();
+            updateInteraction(mapToView(_cursorPosition), true, false, true);
+        }
+        else if (pickPreviewAt(event->position()))
         {
             event->accept();
             _hovering = true;
@@ -57,9 +329,17 @@ class GizmoInteractionItem : public QQuickItem
 
     void hoverMoveEvent(QHoverEvent *event) override
     {
+        _cursorPosition = event->position();
+        if (_modal)
+        {
+            event->accept();
+            updateInteraction(mapToView(_cursorPosition), true, false, true);
+            return;
+        }
+
         // This might be required if an object grab the mouse
         _dragging = false;
-        if (pickPreview(event->position()))
+        if (pickPreviewAt(event->position()))
         {
             event->accept();
             _hovering = true;
@@ -79,13 +359,22 @@ class GizmoInteractionItem : public QQuickItem
 
     void mousePressEvent(QMouseEvent *event) override
     {
+        if (_modal)
+        {
+            event->accept();
+            confirmModalInteraction();
+            return;
+        }
+
         assert(_dragging == false);
 
-        if (pickPreview(event->position()))
+        if (pickPreviewAt(event->position()))
         {
             event->accept();
 
             _dragging = true;
+            // Receive values typed during the drag
+            forceActiveFocus(Qt::MouseFocusReason);
             callUpdateInteraction(event->position(), true);
             setKeepMouseGrab(true);
         }
@@ -111,6 +400,7 @@ class GizmoInteractionItem : public QQuickItem
 
     void mouseMoveEvent(QMouseEvent *event) override
     {
+        _cursorPosition = event->position();
         if (!_dragging)
         {
             event->ignore();
@@ -121,26 +411,262 @@ class GizmoInteractionItem : public QQuickItem
         callUpdateInteraction(event->position());
     }
 
+    void touchEvent(QTouchEvent *event) override
+    {
+        switch (event->type())
+        {
+        case QEvent::TouchBegin:
+        {
+            // Only single point drags are supported
+            if (_dragging || event->points().size() != 1)
+            {
+                event->ignore();
+                return;
+            }
+
+            const QEventPoint &point = event->points().first();
+            QPointF hit;
+            if (!pickAround(point.position(), _touchPickRadius, hit))
+            {
+                event->ignore();
+                return;
+            }
+
+            event->accept();
+            _touchPointId = point.id();
+            // Keep the grabbed handle under the finger for the whole drag
+            _pointerOffset = hit - point.position();
+            _dragging = true;
+            callUpdateInteraction(hit, true);
+            setKeepTouchGrab(true);
+            break;
+        }
+        case QEvent::TouchUpdate:
+        case QEvent::TouchEnd:
+        {
+            const QEventPoint *point = trackedTouchPoint(event);
+            if (!_dragging || !point)
+            {
+                event->ignore();
+                return;
+            }
+
+            event->accept();
+            if (event->type() == QEvent::TouchEnd || point->state() == QEventPoint::Released)
+                endTouchDrag(point->position() + _pointerOffset);
+            else
+                callUpdateInteraction(point->position() + _pointerOffset);
+            break;
+        }
+        case QEvent::TouchCancel:
+            event->accept();
+            touchUngrabEvent();
+            break;
+        default:
+            QQuickItem::touchEvent(event);
+            break;
+        }
+    }
+
+    void touchUngrabEvent() override
+    {
+        if (_touchPointId >= 0 && _dragging)
+            endTouchDrag(_lastPosition);
+    }
+
+    bool event(QEvent *event) override
+    {
+        switch (event->type())
+        {
+        case QEvent::TabletPress:
+        case QEvent::TabletMove:
+        case QEvent::TabletRelease:
+            tabletEvent(static_cast<QTabletEvent *>(event));
+            return event->isAccepted();
+        default:
+            return QQuickItem::event(event);
+        }
+    }
+
+    /// Pen input behaves like a mouse without hover state
+    void tabletEvent(QTabletEvent *event)
+    {
+        switch (event->type())
+        {
+        case QEvent::TabletPress:
+            if (_dragging || !pickPreviewAt(event->position()))
+            {
+                event->ignore();
+                return;
+            }
+            event->accept();
+            _dragging = true;
+            callUpdateInteraction(event->position(), true);
+            break;
+        case QEvent::TabletMove:
+            if (!_dragging)
+            {
+                event->ignore();
+                return;
+            }
+            event->accept();
+            callUpdateInteraction(event->position());
+            break;
+        case QEvent::TabletRelease:
+            if (!_dragging)
+            {
+                event->ignore();
+                return;
+            }
+            event->accept();
+            _dragging = false;
+            callUpdateInteraction(event->position());
+            break;
+        default:
+            event->ignore();
+            break;
+        }
+    }
+
+    void keyPressEvent(QKeyEvent *event) override
+    {
+        if (handleKeyPress(event->key(), event->modifiers().toInt(), event->isAutoRepeat()))
+            event->accept();
+        else
+            QQuickItem::keyPressEvent(event);
+    }
+
     virtual void updateInteraction(QPointF position, bool hovered, bool dragStarted, bool dragging) = 0;
     virtual bool pickPreview(QPointF position) = 0;
+    virtual bool handleKeyPress(int key, int modifiers, bool autoRepeat) = 0;
+    virtual void confirmModalInteraction() = 0;
+    /// Publish the handles through `setGeometryData`
+    virtual void updateGeometryData() = 0;
 
 private:
     bool _hovering = false;
     bool _dragging = false;
+    bool _modal = false;
+    QPointF _cursorPosition;
+    qreal _touchPickRadius = 16.;
+    int _touchPointId = -1;
+    QPointF _pointerOffset;
+    QPointF _lastPosition;
+    QPointer<QQuickItem> _view;
+    QPointer<GizmoStyleBase> _style;
+    QRectF _viewport;
+    QByteArray _geometryVertexData;
+    QByteArray _geometryIndexData;
+    qreal _devicePixelRatio = 1.;
+    QMetaObject::Connection _screenConnection;
+    QMetaObject::Connection _frameConnection;
+
+    void updateDevicePixelRatio()
+    {
+        const auto ratio = window() ? window()->effectiveDevicePixelRatio() : 1.;
+        if (qFuzzyCompare(_devicePixelRatio, ratio))
+            return;
+
+        _devicePixelRatio = ratio;
+        Q_EMIT devicePixelRatioChanged();
+        update();
+    }
+
+    void publishGeometryData()
+    {
+        // Placing the handles in the world is only worth it for a GizmoGeometry
+        if (isSignalConnected(QMetaMethod::fromSignal(&GizmoInteractionItem::geometryDataChanged)))
+            updateGeometryData();
+    }
+
+    bool pickPreviewAt(QPointF position)
+    {
+        return pickPreview(mapToView(position));
+    }
 
     void callUpdateInteraction(QPointF position, bool dragStarted = false)
     {
-        updateInteraction(position, _hovering || _dragging, dragStarted, _dragging);
+        _lastPosition = position;
+        updateInteraction(mapToView(position), _hovering || _dragging, dragStarted, _dragging);
+    }
+
+    /// Look for a handle around `position`, sampling rings up to `radius`
+    bool pickAround(QPointF position, qreal radius, QPointF &hit)
+    {
+        if (pickPreviewAt(position))
+        {
+            hit = position;
+            return true;
+        }
+
+        constexpr int ringCount = 2;
+        constexpr int samplesPerRing = 8;
+        for (int ring = 1; ring <= ringCount; ++ring)
+        {
+            const qreal ringRadius = radius * ring / ringCount;
+            for (int i = 0; i < samplesPerRing; ++i)
+            {
+                const qreal angle = 2. * M_PI * i / samplesPerRing;
+                const QPointF sample = position + QPointF(qCos(angle), qSin(angle)) * ringRadius;
+                if (pickPreviewAt(sample))
+                {
+                    hit = sample;
+                    return true;
+                }
+            }
+        }
+
+        return false;
+    }
+
+    const QEventPoint *trackedTouchPoint(const QTouchEvent *event) const
+    {
+        for (const QEventPoint &point : event->points())
+        {
+            if (point.id() == _touchPointId)
+                return &point;
+        }
+        return nullptr;
+    }
+
+    void endTouchDrag(QPointF position)
+    {
+        _dragging = false;
+        _touchPointId = -1;
+        _pointerOffset = QPointF();
+        callUpdateInteraction(position);
+        setKeepTouchGrab(false);
     }
 };
 
 QSGNode *gizmo_update_paint_node(QSGNode *oldNode,
                                  rust::Slice<std::array<float, 2> const> vertices,
-                                 rust::Slice<std::array<float, 4> const> colors,
+                                 rust::Slice<std::array<std::uint8_t, 4> const> colors,
                                  rust::Slice<std::uint32_t const> indices);
 
 std::size_t extract_target_count_from_qvariant(const QVariant &targets);
 
-void extract_targets_from_qvariant(const QVariant &targets, rust::Slice<QVector3D> positions, rust::Slice<QVector4D> rotations, rust::Slice<QVector3D> scales);
+void extract_dvec3_from_qvariant(const QVariant &value, std::array<double, 3> &vector);
+
+void extract_targets_from_qvariant(const QVariant &targets, rust::Slice<std::array<double, 3>> positions, rust::Slice<std::array<double, 4>> rotations, rust::Slice<bool> eulers, rust::Slice<std::array<double, 3>> scales);
+
+QVariant transforms_to_qvariant(const QVariant &targets, rust::Slice<std::array<double, 3> const> positions, rust::Slice<std::array<double, 4> const> rotations, bool euler, rust::Slice<std::array<double, 3> const> scales);
+
+void extract_target_parents_from_qvariant(const QVariant &targets, rust::Slice<std::array<double, 16>> parents);
+
+void extract_target_constraints_from_qvariant(const QVariant &targets, rust::Slice<std::array<bool, 3>> lockedTranslations, rust::Slice<std::array<bool, 3>> allowedRotations, rust::Slice<std::array<double, 3>> minPositions, rust::Slice<std::array<double, 3>> maxPositions, rust::Slice<QVector3D> minScales, rust::Slice<QVector3D> maxScales);
+
+std::size_t extract_snap_point_count_from_qvariant(const QVariant &snapTargets);
+
+void extract_snap_points_from_qvariant(const QVariant &snapTargets, const std::array<double, 3> &sceneOrigin, rust::Slice<std::array<double, 3>> points, rust::Slice<std::size_t> sources);
+
+QVariant qvariant_list_at(const QVariant &list, std::size_t index);
+
+QVariant qvariant_list_select(const QVariant &list, rust::Slice<std::size_t const> indices);
+
+bool qvariant_list_contains(const QVariant &list, const QVariant &item);
+
+QVariant qvariant_list_concat(const QVariant &first, const QVariant &second);
 
-QVariant transforms_to_qvariant(rust::Slice<QVector3D const> positions, rust::Slice<QVector4D const> rotations, rust::Slice<QVector3D const> scales);
+/// Position of the object of the View3D `view` under `position`, in view coordinates
+bool pick_scene_position(QQuickItem &view, QPointF position, QVector3D &scenePosition);
@@ -0,0 +1,112 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+#pragma once
+
+#include <QtCore/QDebug>
+#include <QtCore/QPointer>
+#include <QtQuick3D/QQuick3DGeometry>
+#include <limits>
+
+#include "gizmo.h"
+
+/// Handles of a gizmo as a QtQuick3D mesh, so they are depth tested against the scene
+class GizmoGeometryBase : public QQuick3DGeometry
+{
+    Q_OBJECT
+
+    /// Gizmo item providing the handles
+    Q_PROPERTY(QQuickItem *gizmo READ gizmo WRITE setGizmo NOTIFY gizmoChanged)
+    /// Multiplier of the handles alpha
+    Q_PROPERTY(float alpha READ alpha WRITE setAlpha NOTIFY alphaChanged)
+
+public:
+    GizmoGeometryBase(QQuick3DObject *parent = nullptr)
+        : QQuick3DGeometry(parent)
+    {
+    }
+    virtual ~GizmoGeometryBase() = default;
+
+    QQuickItem *gizmo() const
+    {
+        return _gizmo;
+    }
+
+    void setGizmo(QQuickItem *gizmo)
+    {
+        if (_gizmo == gizmo)
+            return;
+
+        if (_gizmo)
+            disconnect(_gizmo, nullptr, this, nullptr);
+
+        _gizmo = qobject_cast<GizmoInteractionItem *>(gizmo);
+        if (gizmo && !_gizmo)
+            qWarning() << "GizmoGeometry: gizmo must be a Gizmo item";
+        if (_gizmo)
+            connect(_gizmo, &GizmoInteractionItem::geometryDataChanged, this, &GizmoGeometryBase::updateGeometry);
+
+        Q_EMIT gizmoChanged();
+        updateGeometry();
+    }
+
+    float alpha() const
+    {
+        return _alpha;
+    }
+
+    void setAlpha(float alpha)
+    {
+        if (qFuzzyCompare(_alpha, alpha))
+            return;
+
+        _alpha = alpha;
+        Q_EMIT alphaChanged();
+        updateGeometry();
+    }
+
+Q_SIGNALS:
+    void gizmoChanged();
+    void alphaChanged();
+
+private:
+    QPointer<GizmoInteractionItem> _gizmo;
+    float _alpha = 1.f;
+
+    void updateGeometry()
+    {
+        clear();
+        setStride(GizmoInteractionItem::GeometryVertexStride);
+        setPrimitiveType(QQuick3DGeometry::PrimitiveType::Triangles);
+        addAttribute(QQuick3DGeometry::Attribute::PositionSemantic, 0, QQuick3DGeometry::Attribute::F32Type);
+        addAttribute(QQuick3DGeometry::Attribute::ColorSemantic, 3 * sizeof(float), QQuick3DGeometry::Attribute::F32Type);
+        addAttribute(QQuick3DGeometry::Attribute::IndexSemantic, 0, QQuick3DGeometry::Attribute::U32Type);
+
+        if (_gizmo)
+        {
+            QByteArray vertexData = _gizmo->geometryVertexData();
+            auto *vertices = reinterpret_cast<float *>(vertexData.data());
+            const qsizetype vertexCount = vertexData.size() / GizmoInteractionItem::GeometryVertexStride;
+            constexpr int floatsPerVertex = GizmoInteractionItem::GeometryVertexStride / sizeof(float);
+
+            QVector3D minimum(std::numeric_limits<float>::max(), std::numeric_limits<float>::max(), std::numeric_limits<float>::max());
+            QVector3D maximum = -minimum;
+            for (qsizetype i = 0; i < vertexCount; ++i)
+            {
+                float *vertex = vertices + i * floatsPerVertex;
+                const QVector3D position(vertex[0], vertex[1], vertex[2]);
+                minimum = QVector3D(qMin(minimum.x(), position.x()), qMin(minimum.y(), position.y()), qMin(minimum.z(), position.z()));
+                maximum = QVector3D(qMax(maximum.x(), position.x()), qMax(maximum.y(), position.y()), qMax(maximum.z(), position.z()));
+                // Alpha of the color
+                vertex[6] *= _alpha;
+            }
+
+            setVertexData(vertexData);
+            setIndexData(_gizmo->geometryIndexData());
+            if (vertexCount > 0)
+                setBounds(minimum, maximum);
+        }
+
+        update();
+    }
+};
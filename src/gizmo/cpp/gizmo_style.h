@@ -0,0 +1,25 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+#pragma once
+
+#include <QtCore/QObject>
+
+/// Base of `GizmoStyle`, whose properties are declared on the Rust side. Gizmos hold their style
+/// through it and repaint on the notify signal of any of its properties.
+class GizmoStyleBase : public QObject
+{
+    Q_OBJECT
+
+public:
+    GizmoStyleBase(QObject *parent = nullptr)
+        : QObject(parent)
+    {
+    }
+    virtual ~GizmoStyleBase() = default;
+};
+
+class GizmoStyle;
+
+/// `style` as the `GizmoStyle` it was created as, `GizmoStyleBase` isn't instantiated on its own
+const GizmoStyle &gizmo_style_from_base(const GizmoStyleBase &style);
@@ -0,0 +1,365 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+#include <QtGui/QPointingDevice>
+#include <QtGui/QTabletEvent>
+#include <QtGui/QVector3D>
+#include <QtQml/QQmlComponent>
+#include <QtQml/QQmlEngine>
+#include <QtQuick/QQuickWindow>
+#include <QtTest/QTest>
+#include <array>
+#include <cmath>
+#include <memory>
+#include <optional>
+
+#include "gizmo_tests.h"
+
+namespace {
+
+// Looking down -Z, the X translation handle points to the right of the window center
+constexpr char SceneQml[] = R"(
+import QtQuick
+import com.oliv.gizmo
+
+Window {
+    id: root
+
+    property var lastTransforms: []
+
+    width: 400
+    height: 400
+    visible: true
+
+    Gizmo {
+        objectName: "gizmo"
+        anchors.fill: parent
+
+        cameraPosition: Qt.vector3d(0, 0, 10)
+        cameraRotation: Qt.vector4d(0, 0, 0, 1)
+        cameraVerticalFoV: 60
+        cameraNearPlane: 0.1
+        cameraFarPlane: 100
+
+        translatePlaneEnabled: false
+        translateViewEnabled: false
+        rotateEnabled: false
+        rotateViewEnabled: false
+        scaleEnabled: false
+        scalePlaneEnabled: false
+        scaleUniformEnabled: false
+
+        targets: [{ position: Qt.vector3d(0, 0, 0) }]
+
+        onTransformUpdated: (transforms) => root.lastTransforms = transforms
+    }
+}
+)";
+
+// A target kilometres away from the origin, given in double precision under a parent translated by
+// 1000 km. The scene is rendered around it, so it is drawn at the window center.
+constexpr char GeospatialSceneQml[] = R"(
+import QtQuick
+import com.oliv.gizmo
+
+Window {
+    id: root
+
+    property var lastTransforms: []
+
+    width: 400
+    height: 400
+    visible: true
+
+    Gizmo {
+        objectName: "gizmo"
+        anchors.fill: parent
+
+        sceneOrigin: [4200000.123456789, 0.987654321, 4200000.75]
+        cameraPosition: Qt.vector3d(0, 0, 10)
+        cameraRotation: Qt.vector4d(0, 0, 0, 1)
+        cameraVerticalFoV: 60
+        cameraNearPlane: 0.1
+        cameraFarPlane: 100
+
+        translatePlaneEnabled: false
+        translateViewEnabled: false
+        rotateEnabled: false
+        rotateViewEnabled: false
+        scaleEnabled: false
+        scalePlaneEnabled: false
+        scaleUniformEnabled: false
+
+        maxPosition: [4200000.173456789, 1e9, 1e9]
+
+        targets: [{
+            position: [3200000.123456789, 0.987654321, 4200000.75],
+            rotation: [0, 0, 0, 1],
+            scale: [1, 1, 1],
+            parentTransform: [1, 0, 0, 1000000, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]
+        }]
+
+        onTransformUpdated: (transforms) => root.lastTransforms = transforms
+    }
+}
+)";
+
+/// Distance along X from the window center to a point of the X translation handle
+constexpr int HandleOffset = 45;
+/// Distance the drags move the pointer by
+constexpr int DragDistance = 40;
+/// Distance along X from the window center past the end of the X translation handle
+constexpr int PastHandleOffset = 120;
+
+std::unique_ptr<QQuickWindow> createScene(QQmlEngine &engine, QString &error, const char *qml = SceneQml)
+{
+    QQmlComponent component(&engine);
+    component.setData(qml, QUrl());
+    std::unique_ptr<QQuickWindow> window(qobject_cast<QQuickWindow *>(component.create()));
+    if (!window)
+    {
+        error = component.errorString();
+        return nullptr;
+    }
+    if (!QTest::qWaitForWindowExposed(window.get()))
+    {
+        error = QStringLiteral("the window wasn't exposed");
+        return nullptr;
+    }
+    return window;
+}
+
+QPoint handlePosition(const QQuickWindow &window)
+{
+    return QPoint(window.width() / 2 + HandleOffset, window.height() / 2);
+}
+
+/// Position of the target sent by the last `transformUpdated`, if any
+std::optional<QVector3D> lastTargetPosition(const QQuickWindow &window)
+{
+    const QVariantList transforms = window.property("lastTransforms").toList();
+    if (transforms.isEmpty())
+        return std::nullopt;
+    return transforms.first().toMap().value("position").value<QVector3D>();
+}
+
+/// Check the target was dragged right along X
+QString checkDraggedAlongX(const QQuickWindow &window)
+{
+    const auto position = lastTargetPosition(window);
+    if (!position)
+        return QStringLiteral("the target wasn't moved");
+    if (position->x() <= 0.f || !qFuzzyIsNull(position->y()) || !qFuzzyIsNull(position->z()))
+        return QStringLiteral("the target was moved to (%1, %2, %3) instead of along +X")
+            .arg(position->x())
+            .arg(position->y())
+            .arg(position->z());
+    return QString();
+}
+
+QString checkNotMoved(const QQuickWindow &window)
+{
+    if (lastTargetPosition(window))
+        return QStringLiteral("the target was moved");
+    return QString();
+}
+
+QPointingDevice *touchscreen()
+{
+    static QPointingDevice *device = QTest::createTouchDevice();
+    return device;
+}
+
+/// Send a pen event to `window` as the windowing system would, returning whether it was accepted
+bool sendTabletEvent(QQuickWindow &window, QEvent::Type type, QPoint position)
+{
+    static const QPointingDevice stylus(QStringLiteral("stylus"), 1, QInputDevice::DeviceType::Stylus,
+                                        QPointingDevice::PointerType::Pen,
+                                        QInputDevice::Capability::Position | QInputDevice::Capability::Pressure,
+                                        1, 3);
+
+    const Qt::MouseButton button = type == QEvent::TabletMove ? Qt::NoButton : Qt::LeftButton;
+    const Qt::MouseButtons buttons = type == QEvent::TabletRelease ? Qt::NoButton : Qt::LeftButton;
+    QTabletEvent event(type, &stylus, position, window.mapToGlobal(QPointF(position)),
+                       buttons ? 0.5 : 0., 0.f, 0.f, 0.f, 0., 0.f, Qt::NoModifier, button, buttons);
+    QCoreApplication::sendEvent(&window, &event);
+    return event.isAccepted();
+}
+
+rust::String toRustString(const QString &error)
+{
+    return rust::String(error.toStdString());
+}
+
+} // namespace
+
+rust::String touch_drag_moves_target()
+{
+    QQmlEngine engine;
+    QString error;
+    const auto window = createScene(engine, error);
+    if (!window)
+        return toRustString(error);
+
+    // Off the handle, but within the touch pick radius
+    const QPoint press = handlePosition(*window) + QPoint(0, 8);
+    const QPoint release = press + QPoint(DragDistance, 0);
+    QTest::touchEvent(window.get(), touchscreen()).press(0, press, window.get());
+    QTest::touchEvent(window.get(), touchscreen()).move(0, release, window.get());
+    QTest::touchEvent(window.get(), touchscreen()).release(0, release, window.get());
+
+    return toRustString(checkDraggedAlongX(*window));
+}
+
+rust::String touch_outside_handles_is_ignored()
+{
+    QQmlEngine engine;
+    QString error;
+    const auto window = createScene(engine, error);
+    if (!window)
+        return toRustString(error);
+
+    const QPoint press(10, 10);
+    QTest::touchEvent(window.get(), touchscreen()).press(0, press, window.get());
+    QTest::touchEvent(window.get(), touchscreen()).move(0, press + QPoint(DragDistance, 0), window.get());
+    QTest::touchEvent(window.get(), touchscreen()).release(0, press + QPoint(DragDistance, 0), window.get());
+
+    return toRustString(checkNotMoved(*window));
+}
+
+rust::String touch_second_point_is_ignored()
+{
+    QQmlEngine engine;
+    QString error;
+    const auto window = createScene(engine, error);
+    if (!window)
+        return toRustString(error);
+
+    const QPoint press = handlePosition(*window);
+    const QPoint release = press + QPoint(DragDistance, 0);
+    const QPoint secondPress(10, 10);
+    // The second finger moves the other way, the target must follow the first one only
+    const QPoint secondRelease = secondPress + QPoint(0, 3 * DragDistance);
+    QTest::touchEvent(window.get(), touchscreen()).press(0, press, window.get());
+    QTest::touchEvent(window.get(), touchscreen()).stationary(0).press(1, secondPress, window.get());
+    QTest::touchEvent(window.get(), touchscreen())
+        .move(0, release, window.get())
+        .move(1, secondRelease, window.get());
+    QTest::touchEvent(window.get(), touchscreen()).stationary(0).release(1, secondRelease, window.get());
+    QTest::touchEvent(window.get(), touchscreen()).release(0, release, window.get());
+
+    return toRustString(checkDraggedAlongX(*window));
+}
+
+rust::String tablet_drag_moves_target()
+{
+    QQmlEngine engine;
+    QString error;
+    const auto window = createScene(engine, error);
+    if (!window)
+        return toRustString(error);
+
+    const QPoint press = handlePosition(*window);
+    const QPoint release = press + QPoint(DragDistance, 0);
+    // Accepted events prove the gizmo handles the pen itself rather than the mouse events Qt
+    // synthesizes from ignored ones
+    if (!sendTabletEvent(*window, QEvent::TabletPress, press))
+        return toRustString(QStringLiteral("the pen press on the handle wasn't accepted"));
+    if (!sendTabletEvent(*window, QEvent::TabletMove, release))
+        return toRustString(QStringLiteral("the pen move during the drag wasn't accepted"));
+    if (!sendTabletEvent(*window, QEvent::TabletRelease, release))
+        return toRustString(QStringLiteral("the pen release wasn't accepted"));
+
+    return toRustString(checkDraggedAlongX(*window));
+}
+
+rust::String tablet_outside_handles_is_ignored()
+{
+    QQmlEngine engine;
+    QString error;
+    const auto window = createScene(engine, error);
+    if (!window)
+        return toRustString(error);
+
+    const QPoint press(10, 10);
+    if (sendTabletEvent(*window, QEvent::TabletPress, press))
+        return toRustString(QStringLiteral("the pen press outside the handles was accepted"));
+    sendTabletEvent(*window, QEvent::TabletMove, press + QPoint(DragDistance, 0));
+    sendTabletEvent(*window, QEvent::TabletRelease, press + QPoint(DragDistance, 0));
+
+    return toRustString(checkNotMoved(*window));
+}
+
+rust::String scaled_window_picks_in_logical_pixels(double scaleFactor)
+{
+    QQmlEngine engine;
+    QString error;
+    const auto window = createScene(engine, error);
+    if (!window)
+        return toRustString(error);
+
+    if (!qFuzzyCompare(window->effectiveDevicePixelRatio(), scaleFactor))
+        return toRustString(QStringLiteral("the window device pixel ratio is %1 instead of %2")
+                                .arg(window->effectiveDevicePixelRatio())
+                                .arg(scaleFactor));
+    const auto *gizmo = window->findChild<QQuickItem *>(QStringLiteral("gizmo"));
+    const qreal ratio = gizmo->property("devicePixelRatio").toReal();
+    if (!qFuzzyCompare(ratio, scaleFactor))
+        return toRustString(
+            QStringLiteral("the gizmo device pixel ratio is %1 instead of %2").arg(ratio).arg(scaleFactor));
+
+    // A gizmo sized in physical pixels would reach past the handle end at higher ratios
+    const QPoint pastHandle(window->width() / 2 + PastHandleOffset, window->height() / 2);
+    QTest::mousePress(window.get(), Qt::LeftButton, Qt::NoModifier, pastHandle);
+    QTest::mouseMove(window.get(), pastHandle + QPoint(DragDistance, 0));
+    QTest::mouseRelease(window.get(), Qt::LeftButton, Qt::NoModifier, pastHandle + QPoint(DragDistance, 0));
+    error = checkNotMoved(*window);
+    if (!error.isEmpty())
+        return toRustString(QStringLiteral("press past the handle end: %1").arg(error));
+
+    const QPoint press = handlePosition(*window);
+    const QPoint release = press + QPoint(DragDistance, 0);
+    QTest::mousePress(window.get(), Qt::LeftButton, Qt::NoModifier, press);
+    QTest::mouseMove(window.get(), release);
+    QTest::mouseRelease(window.get(), Qt::LeftButton, Qt::NoModifier, release);
+
+    return toRustString(checkDraggedAlongX(*window));
+}
+
+rust::String geospatial_drag_keeps_double_precision()
+{
+    QQmlEngine engine;
+    QString error;
+    const auto window = createScene(engine, error, GeospatialSceneQml);
+    if (!window)
+        return toRustString(error);
+
+    // Far enough to reach `maxPosition`, 5 cm away along X
+    const QPoint press = handlePosition(*window);
+    const QPoint release = press + QPoint(DragDistance, 0);
+    QTest::mousePress(window.get(), Qt::LeftButton, Qt::NoModifier, press);
+    QTest::mouseMove(window.get(), release);
+    QTest::mouseRelease(window.get(), Qt::LeftButton, Qt::NoModifier, release);
+
+    const QVariantList transforms = window->property("lastTransforms").toList();
+    if (transforms.isEmpty())
+        return toRustString(QStringLiteral("the target wasn't moved"));
+    const QVariant position = transforms.first().toMap().value("position");
+    if (position.metaType() != QMetaType::fromType<QVariantList>() || position.toList().size() != 3)
+        return toRustString(QStringLiteral("the position isn't an array of 3 numbers anymore"));
+
+    // Single precision is off by more than 10 cm at these magnitudes
+    const QVariantList components = position.toList();
+    const std::array<double, 3> expected{3200000.173456789, 0.987654321, 4200000.75};
+    const std::array<double, 3> tolerances{1e-6, 1e-9, 1e-9};
+    for (int i = 0; i < 3; ++i)
+    {
+        const double component = components.at(i).toDouble();
+        if (std::abs(component - expected[std::size_t(i)]) > tolerances[std::size_t(i)])
+            return toRustString(QStringLiteral("component %1 of the position is %2 instead of %3")
+                                    .arg(i)
+                                    .arg(component, 0, 'f', 9)
+                                    .arg(expected[std::size_t(i)], 0, 'f', 9));
+    }
+    return rust::String();
+}
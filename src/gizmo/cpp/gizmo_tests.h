@@ -0,0 +1,33 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+#pragma once
+
+#include "rust/cxx.h"
+
+// Scenarios driving a Gizmo shown in an offscreen window with synthesized input events.
+// They need a running QGuiApplication and return a description of the first failed check, or an
+// empty string on success.
+
+/// A touch pressed next to the X translation handle grabs it and drags the target along X
+rust::String touch_drag_moves_target();
+
+/// A touch far from the handles is left to the items below the gizmo
+rust::String touch_outside_handles_is_ignored();
+
+/// A second finger doesn't disturb the drag of the first one
+rust::String touch_second_point_is_ignored();
+
+/// Pen press, move and release on the X translation handle drag the target along X
+rust::String tablet_drag_moves_target();
+
+/// A pen press far from the handles is left to the items below the gizmo
+rust::String tablet_outside_handles_is_ignored();
+
+/// At a device pixel ratio of `scaleFactor`, set through `QT_SCALE_FACTOR`, the gizmo follows the
+/// ratio of its window and keeps the same size and picking in logical pixels
+rust::String scaled_window_picks_in_logical_pixels(double scaleFactor);
+
+/// A target kilometres away from the origin, given in double precision under a parent, is dragged
+/// against `maxPosition` around a `sceneOrigin` and written back without losing precision
+rust::String geospatial_drag_keeps_double_precision();
@@ -0,0 +1,54 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+#pragma once
+
+#include <QtGui/QMouseEvent>
+#include <QtQuick/QQuickItem>
+
+#include "gizmo.h"
+
+/// Mouse handling of the marquee selector, the selection is computed from rust
+class MarqueeInteractionItem : public QQuickItem
+{
+    Q_OBJECT
+
+public:
+    MarqueeInteractionItem(QQuickItem *parent = nullptr)
+        : QQuickItem(parent)
+    {
+        setAcceptedMouseButtons(Qt::LeftButton);
+    }
+    virtual ~MarqueeInteractionItem() = default;
+
+protected:
+    void mousePressEvent(QMouseEvent *event) override
+    {
+        _pressPosition = event->position();
+        updateMarquee(_pressPosition, _pressPosition);
+    }
+
+    void mouseMoveEvent(QMouseEvent *event) override
+    {
+        updateMarquee(_pressPosition, event->position());
+    }
+
+    void mouseReleaseEvent(QMouseEvent *event) override
+    {
+        finishMarquee(_pressPosition, event->position(), event->modifiers().toInt());
+    }
+
+    void mouseUngrabEvent() override
+    {
+        cancelMarquee();
+    }
+
+    /// The rectangle is being dragged from `start` to `end`
+    virtual void updateMarquee(QPointF start, QPointF end) = 0;
+    /// Select what is inside the rectangle from `start` to `end`
+    virtual void finishMarquee(QPointF start, QPointF end, int modifiers) = 0;
+    virtual void cancelMarquee() = 0;
+
+private:
+    QPointF _pressPosition;
+};
@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+#pragma once
+
+#include <QtCore/QPointer>
+#include <QtGui/QMouseEvent>
+#include <QtGui/QVector3D>
+#include <QtQuick/QQuickItem>
+#include <QtQuick/QSGNode>
+
+#include "gizmo.h"
+
+/// Mouse handling of the measure tool, clicked points are placed in the scene from rust
+class MeasureInteractionItem : public QQuickItem
+{
+    Q_OBJECT
+
+    /// View3D whose objects are measured. Points are placed on the ground when it isn't set or
+    /// when there is no object under the cursor.
+    Q_PROPERTY(QQuickItem *view READ view WRITE setView NOTIFY viewChanged)
+
+public:
+    MeasureInteractionItem(QQuickItem *parent = nullptr)
+        : QQuickItem(parent)
+    {
+        setAcceptedMouseButtons(Qt::LeftButton);
+    }
+    virtual ~MeasureInteractionItem() = default;
+
+    QQuickItem *view() const
+    {
+        return _view;
+    }
+
+    void setView(QQuickItem *view)
+    {
+        if (_view == view)
+            return;
+
+        _view = view;
+        Q_EMIT viewChanged();
+    }
+
+    /// Draw `texts` centered on `positions` as children of `node`
+    void updateLabels(QSGNode *node, rust::Slice<QPointF const> positions, rust::Slice<QString const> texts)
+    {
+        if (node)
+            update_label_nodes(*this, *node, positions, texts);
+    }
+
+Q_SIGNALS:
+    void viewChanged();
+
+protected:
+    void mousePressEvent(QMouseEvent *event) override
+    {
+        _pressPosition = event->position();
+    }
+
+    void mouseReleaseEvent(QMouseEvent *event) override
+    {
+        // Dragging doesn't place points
+        if ((event->position() - _pressPosition).manhattanLength() > ClickDistance)
+            return;
+
+        QVector3D scenePosition;
+        const bool hit = _view && pick_scene_position(*_view, mapToItem(_view, event->position()), scenePosition);
+        pointClicked(event->position(), hit, scenePosition);
+    }
+
+    /// Place a point clicked at `position`, on `scenePosition` when the click `hit` an object
+    virtual void pointClicked(QPointF position, bool hit, QVector3D scenePosition) = 0;
+
+private:
+    static constexpr qreal ClickDistance = 4.;
+
+    QPointer<QQuickItem> _view;
+    QPointF _pressPosition;
+};
@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+#pragma once
+
+#include <QtCore/QEasingCurve>
+#include <QtCore/QVariantAnimation>
+#include <QtGui/QQuaternion>
+#include <QtGui/QVector4D>
+#include <QtQuick/QQuickItem>
+
+/// Mouse handling of the view cube, the parts of the cube are picked from rust
+class ViewCubeInteractionItem : public QQuickItem
+{
+    Q_OBJECT
+
+    /// Duration in milliseconds of the transition to a requested orientation, 0 to jump to it
+    Q_PROPERTY(int animationDuration READ animationDuration WRITE setAnimationDuration NOTIFY animationDurationChanged)
+
+public:
+    ViewCubeInteractionItem(QQuickItem *parent = nullptr)
+        : QQuickItem(parent)
+    {
+        setAcceptHoverEvents(true);
+        setAcceptedMouseButtons(Qt::LeftButton);
+
+        _animation.setEasingCurve(QEasingCurve::OutCubic);
+        connect(&_animation, &QVariantAnimation::valueChanged, this, [this](const QVariant &value)
+                { Q_EMIT orientationRequested(value.value<QQuaternion>()); });
+    }
+    virtual ~ViewCubeInteractionItem() = default;
+
+    int animationDuration() const
+    {
+        return _animationDuration;
+    }
+
+    void setAnimationDuration(int duration)
+    {
+        if (_animationDuration == duration)
+            return;
+
+        _animationDuration = duration;
+        Q_EMIT animationDurationChanged();
+    }
+
+    /// Request the camera to go from the `from` rotation to the `to` one, both as (x, y, z, w)
+    void requestOrientation(QVector4D from, QVector4D to)
+    {
+        _animation.stop();
+
+        const QQuaternion target(to.w(), to.x(), to.y(), to.z());
+        if (_animationDuration <= 0)
+        {
+            Q_EMIT orientationRequested(target);
+            return;
+        }
+
+        _animation.setDuration(_animationDuration);
+        _animation.setStartValue(QQuaternion(from.w(), from.x(), from.y(), from.z()));
+        _animation.setEndValue(target);
+        _animation.start();
+    }
+
+Q_SIGNALS:
+    void animationDurationChanged();
+    /// Emitted with the rotation the camera should take, several times during a transition
+    void orientationRequested(QQuaternion rotation);
+
+protected:
+    void hoverEnterEvent(QHoverEvent *event) override
+    {
+        hover(event->position());
+    }
+
+    void hoverMoveEvent(QHoverEvent *event) override
+    {
+        hover(event->position());
+    }
+
+    void hoverLeaveEvent(QHoverEvent *) override
+    {
+        clearHover();
+    }
+
+    void mousePressEvent(QMouseEvent *event) override
+    {
+        // Let the click go through the transparent corners of the item
+        if (!hover(event->position()))
+            event->ignore();
+    }
+
+    void mouseReleaseEvent(QMouseEvent *event) override
+    {
+        if (hover(event->position()))
+            activate();
+    }
+
+    /// Highlight the part of the cube under `position`, return whether there is one
+    virtual bool hover(QPointF position) = 0;
+    virtual void clearHover() = 0;
+    /// Request the orientation looking at the hovered part
+    virtual void activate() = 0;
+
+private:
+    int _animationDuration = 0;
+    QVariantAnimation _animation;
+};
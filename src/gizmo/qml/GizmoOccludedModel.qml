@@ -0,0 +1,31 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+import QtQuick
+import QtQuick3D
+
+import com.oliv.gizmo
+
+// Parts of the handles of `gizmo` hidden by the scene, drawn dimmed.
+// Place it in the scene next to the Model rendering a GizmoGeometry, sharing its geometry. Each
+// fragment is compared with the depth texture of the View3D, so only the hidden parts are drawn.
+Model {
+    id: root
+
+    required property Gizmo gizmo
+    // Multiplier of the handles alpha
+    property real alpha: 0.3
+
+    visible: gizmo.visible
+    materials: CustomMaterial {
+        property real occludedAlpha: root.alpha
+
+        shadingMode: CustomMaterial.Unshaded
+        sourceBlend: CustomMaterial.SrcAlpha
+        destinationBlend: CustomMaterial.OneMinusSrcAlpha
+        depthDrawMode: Material.NeverDepthDraw
+        cullMode: Material.NoCulling
+        vertexShader: "occluded_handles.vert"
+        fragmentShader: "occluded_handles.frag"
+    }
+}
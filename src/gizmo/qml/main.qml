@@ -16,14 +16,29 @@ Window {
     height: 720
     visible: true
 
+    color: "#455A64"
+
+    // Drawn below the transparent view, so the scene objects hide it
+    GroundGrid {
+        anchors.fill: view
+        cameraPosition: camera.position
+        cameraRotation: camera.rotation.toVector4d()
+        cameraVerticalFoV: camera.fieldOfView
+        cameraNearPlane: camera.clipNear
+        cameraFarPlane: camera.clipFar
+        xColor: gizmo.xColor
+        zColor: gizmo.zColor
+        // Show where absolute snapping puts the targets
+        snapDistance: gizmo.snapping && gizmo.snapMode === Gizmo.AbsoluteGrid ? gizmo.snapDistance : 0
+    }
+
     View3D {
         id: view
         property list<Model> pickedModels: [cube1]
         anchors.fill: parent
 
         environment: SceneEnvironment {
-            clearColor: "#455A64"
-            backgroundMode: SceneEnvironment.Color
+            backgroundMode: SceneEnvironment.Transparent
         }
         PerspectiveCamera {
             id: camera
@@ -86,7 +101,27 @@ Window {
 
             // onYChanged: () => gizmo.updateTargets()
         }
-        AxisHelper {}
+
+        // Gizmo rendered in the scene, hidden by objects in front of it
+        Model {
+            visible: gizmo3dCb.checked && gizmo.visible
+            geometry: GizmoGeometry {
+                id: gizmoGeometry
+                gizmo: gizmo
+            }
+            materials: DefaultMaterial {
+                vertexColorsEnabled: true
+                lighting: DefaultMaterial.NoLighting
+                cullMode: Material.NoCulling
+            }
+        }
+
+        // Parts of the gizmo hidden by the objects in front of it, dimmed
+        GizmoOccludedModel {
+            visible: gizmo3dCb.checked && gizmo.visible
+            gizmo: gizmo
+            geometry: gizmoGeometry
+        }
     }
 
     WasdController {
@@ -127,8 +162,11 @@ Window {
     Gizmo {
         id: gizmo
         visible: view.pickedModels.length > 0
+        // Receive G/R/S modal transform shortcuts
+        focus: true
 
         anchors.fill: parent
+        view: view
 
         cameraPosition: camera.position
         cameraRotation: camera.rotation.toVector4d()
@@ -147,17 +185,28 @@ Window {
         scalePlaneEnabled: scalePlaneCb.checked
         scaleUniformEnabled: scaleUniformCb.checked
 
-        orientation: localGizmo.checked ? Gizmo.Local : Gizmo.Global
+        orientation: orientationCb.currentValue
         pivotPoint: pivotIndividualOrigin.checked ? Gizmo.IndividualOrigins : Gizmo.MedianPoint
 
+        lockTranslationY: lockYCb.checked
+        allowRotationX: !yawOnlyCb.checked
+        allowRotationZ: !yawOnlyCb.checked
+
         snapping: snappingCb.checked
+        snapMode: absoluteSnapCb.checked ? Gizmo.AbsoluteGrid : Gizmo.Relative
         snapDistance: parseFloat(snapDistanceTf.text.replace(",", ".")) * 100
         snapAngle: parseFloat(snapAngleTf.text.replace(",", ".")) * Math.PI / 180
         snapScale: parseFloat(snapScaleTf.text.replace(",", "."))
-        pixelsPerPoint: Screen.devicePixelRatio
 
+        snapTargets: snapToObjectsCb.checked ? [cube1, cube2].filter(model => view.pickedModels.indexOf(model) === -1) : []
+
+        cursorFeedback: cursorFeedbackCb.checked
+
+        overlayEnabled: !gizmo3dCb.checked
         strokeWidth: strokeWidthSlider.value
         gizmoSize: gizmoSizeSlider.value
+        style: hoverStyleCb.checked ? hoverStyle : null
+        showLabels: labelsCb.checked
 
         function updateTargets() {
             let newTargets = [];
@@ -166,7 +215,7 @@ Window {
                 const model = view.pickedModels[i];
                 newTargets.push({
                     position: model.position,
-                    rotation: model.rotation.toVector4d(),
+                    rotation: model.rotation,
                     scale: model.scale
                 });
             }
@@ -184,8 +233,7 @@ Window {
                 const model = view.pickedModels[i];
 
                 model.position = transform.position;
-                // IMPORTANT: quaternion expect the scalar part first in qt's api
-                model.rotation = Qt.quaternion(transform.rotation.w, transform.rotation.x, transform.rotation.y, transform.rotation.z);
+                model.rotation = transform.rotation;
                 model.scale = transform.scale;
             }
         }
@@ -195,10 +243,110 @@ Window {
         }
     }
 
+    GizmoStyle {
+        id: hoverStyle
+        xHoverColor: "#ffeb3b"
+        yHoverColor: "#ffeb3b"
+        zHoverColor: "#ffeb3b"
+        sHoverColor: "#ffeb3b"
+        inactiveAlpha: 0.5
+        strokeWidth: strokeWidthSlider.value
+        translateGizmoSize: gizmoSizeSlider.value
+        // A rotation only gizmo reads better slightly larger
+        rotateGizmoSize: gizmoSizeSlider.value * 1.2
+        scaleGizmoSize: gizmoSizeSlider.value
+    }
+
+    // Placing points takes over the clicks of the gizmo
+    MeasureTool {
+        id: measureTool
+        anchors.fill: view
+        enabled: measureEnabledCb.checked
+        view: view
+
+        cameraPosition: camera.position
+        cameraRotation: camera.rotation.toVector4d()
+        cameraVerticalFoV: camera.fieldOfView
+        cameraNearPlane: camera.clipNear
+        cameraFarPlane: camera.clipFar
+        unit: "cm"
+        closed: measureClosedCb.checked
+    }
+
+    MarqueeSelector {
+        anchors.fill: view
+        enabled: marqueeCb.checked
+
+        cameraPosition: camera.position
+        cameraRotation: camera.rotation.toVector4d()
+        cameraVerticalFoV: camera.fieldOfView
+        cameraNearPlane: camera.clipNear
+        cameraFarPlane: camera.clipFar
+
+        nodes: [cube1, cube2]
+        selection: Array.from(view.pickedModels)
+        containment: marqueeBoundsCb.checked ? MarqueeSelector.Bounds : MarqueeSelector.Origin
+        onSelected: (nodes) => {
+            view.pickedModels = nodes;
+            gizmo.updateTargets();
+        }
+    }
+
     DebugView {
         source: view
     }
 
+    Label {
+        anchors.bottom: parent.bottom
+        anchors.horizontalCenter: parent.horizontalCenter
+        anchors.bottomMargin: 20
+        visible: gizmo.activeMode !== Gizmo.NoOverride
+        font.pixelSize: 18
+        text: {
+            if (gizmo.numericInput !== "")
+                return `Value: ${gizmo.numericInput}`;
+
+            // Scene units are centimeters in this demo
+            const t = gizmo.totalTranslation;
+            const s = gizmo.totalScale;
+            switch (gizmo.activeMode) {
+            case Gizmo.TranslateX:
+            case Gizmo.TranslateY:
+            case Gizmo.TranslateZ:
+            case Gizmo.TranslateXY:
+            case Gizmo.TranslateXZ:
+            case Gizmo.TranslateYZ:
+            case Gizmo.TranslateView:
+                return `ΔX ${t.x.toFixed(1)} cm  ΔY ${t.y.toFixed(1)} cm  ΔZ ${t.z.toFixed(1)} cm`;
+            case Gizmo.RotateX:
+            case Gizmo.RotateY:
+            case Gizmo.RotateZ:
+            case Gizmo.RotateView:
+                return `Rotated ${(gizmo.totalRotationAngle * 180 / Math.PI).toFixed(1)}°`;
+            default:
+                return `Scale ${s.x.toFixed(2)} × ${s.y.toFixed(2)} × ${s.z.toFixed(2)}`;
+            }
+        }
+    }
+
+    ViewCube {
+        anchors.left: parent.left
+        anchors.bottom: parent.bottom
+        anchors.margins: 20
+        width: 120
+        height: 120
+
+        cameraRotation: camera.rotation.toVector4d()
+        animationDuration: 300
+
+        // Orbit around the world origin
+        onOrientationRequested: (rotation) => {
+            const distance = camera.position.length();
+            camera.rotation = rotation;
+            camera.position = rotation.times(Qt.vector3d(0, 0, distance));
+        }
+    }
+
     Pane {
         anchors.right: parent.right
         ColumnLayout {
@@ -256,16 +404,48 @@ Window {
             Label {
                 text: "Config:"
             }
-            CheckBox {
-                id: localGizmo
-                Layout.fillWidth: true
-                text: "Local Gizmo"
+            RowLayout {
+                Label {
+                    Layout.fillWidth: true
+                    Layout.alignment: Qt.AlignVCenter
+                    text: "Orientation"
+                }
+                ComboBox {
+                    id: orientationCb
+                    Layout.preferredWidth: 100
+                    textRole: "text"
+                    valueRole: "value"
+                    model: [
+                        { text: "Global", value: Gizmo.Global },
+                        { text: "Local", value: Gizmo.Local },
+                        { text: "View", value: Gizmo.View }
+                    ]
+                }
             }
             CheckBox {
                 id: pivotIndividualOrigin
                 Layout.fillWidth: true
                 text: "Pivot Individual Origin"
             }
+            CheckBox {
+                id: cursorFeedbackCb
+                Layout.fillWidth: true
+                text: "Cursor Feedback"
+                checked: true
+            }
+            Label {
+                text: "Constraints:"
+            }
+            CheckBox {
+                id: lockYCb
+                Layout.fillWidth: true
+                text: "Lock Translation Y"
+            }
+            CheckBox {
+                id: yawOnlyCb
+                Layout.fillWidth: true
+                text: "Rotate Around Y Only"
+            }
             Label {
                 text: "Snap:"
             }
@@ -274,6 +454,16 @@ Window {
                 Layout.fillWidth: true
                 text: "Snapping"
             }
+            CheckBox {
+                id: absoluteSnapCb
+                Layout.fillWidth: true
+                text: "Absolute Grid"
+            }
+            CheckBox {
+                id: snapToObjectsCb
+                Layout.fillWidth: true
+                text: "Snap To Objects"
+            }
             RowLayout {
                 Label {
                     Layout.fillWidth: true
@@ -334,6 +524,22 @@ Window {
             Label {
                 text: "Visuals:"
             }
+            CheckBox {
+                id: gizmo3dCb
+                Layout.fillWidth: true
+                text: "Render In Scene"
+            }
+            CheckBox {
+                id: hoverStyleCb
+                Layout.fillWidth: true
+                text: "Hover Style"
+            }
+            CheckBox {
+                id: labelsCb
+                Layout.fillWidth: true
+                text: "Show Labels"
+                checked: true
+            }
             RowLayout {
                 Label {
                     Layout.fillWidth: true
@@ -364,6 +570,49 @@ Window {
                     value: 75
                 }
             }
+            Label {
+                text: "Selection:"
+            }
+            CheckBox {
+                id: marqueeCb
+                Layout.fillWidth: true
+                text: "Box Select"
+            }
+            CheckBox {
+                id: marqueeBoundsCb
+                Layout.fillWidth: true
+                text: "Whole Bounds Inside"
+            }
+            Label {
+                text: "Measure:"
+            }
+            RowLayout {
+                CheckBox {
+                    id: measureClosedCb
+                    Layout.fillWidth: true
+                    text: "Closed"
+                }
+                Button {
+                    text: "Clear"
+                    enabled: measureTool.pointCount > 0
+                    onClicked: measureTool.clear()
+                }
+            }
+            CheckBox {
+                id: measureEnabledCb
+                Layout.fillWidth: true
+                text: "Place Points"
+            }
+            Label {
+                visible: measureTool.pointCount > 1
+                // Scene units are centimeters in this demo
+                text: {
+                    let text = `Distance ${measureTool.distance.toFixed(1)} cm`;
+                    if (measureTool.pointCount > 2)
+                        text += `\nAngle ${measureTool.angle.toFixed(1)}°\nArea ${measureTool.area.toFixed(1)} cm²`;
+                    return text;
+                }
+            }
         }
     }
 }
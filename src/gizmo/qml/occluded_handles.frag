@@ -0,0 +1,21 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+VARYING vec4 handleColor;
+VARYING vec4 handlePosition;
+
+void MAIN()
+{
+    vec2 uv = FRAGCOORD.xy / vec2(textureSize(DEPTH_TEXTURE, 0));
+    float sceneDepth = texture(DEPTH_TEXTURE, uv).r;
+    float depth = handlePosition.z / handlePosition.w;
+    // Depth textures go from 0 to 1, normalized device coordinates from -1 with OpenGL
+    if (NEAR_CLIP_VALUE < 0.0)
+        depth = depth * 0.5 + 0.5;
+
+    // Visible parts are drawn by the GizmoGeometry model, which is in the depth texture too
+    if (depth <= sceneDepth + 0.00001)
+        discard;
+
+    FRAGCOLOR = vec4(handleColor.rgb, handleColor.a * occludedAlpha);
+}
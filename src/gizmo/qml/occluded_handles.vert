@@ -0,0 +1,14 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+VARYING vec4 handleColor;
+VARYING vec4 handlePosition;
+
+void MAIN()
+{
+    handleColor = COLOR;
+    handlePosition = MODELVIEWPROJECTION_MATRIX * vec4(VERTEX, 1.0);
+    POSITION = handlePosition;
+    // Pass the depth test against the scene, the fragment shader compares the actual depth
+    POSITION.z = mix(NEAR_CLIP_VALUE, 1.0, 0.001) * POSITION.w;
+}
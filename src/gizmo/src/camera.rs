@@ -0,0 +1,183 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Camera math shared by the items drawn over a QtQuick3D view.
+//!
+//! The items don't have access to the QtQuick3D camera, they get a copy of its properties
+//! (`cameraPosition`, `cameraRotation`, `cameraVerticalFoV`, `cameraNearPlane` and
+//! `cameraFarPlane`) from QML.
+//!
+//! QtQuick3D works in single precision. Scenes far from the origin are rendered around a
+//! `sceneOrigin` instead: the camera and the scene nodes are relative to it, while world positions
+//! are in double precision.
+
+use cxx_qt_lib::{QVector3D, QVector4D};
+
+pub struct Camera {
+    /// World position of the scene origin
+    pub origin: glam::DVec3,
+    /// Position in the scene
+    pub position: glam::Vec3,
+    pub rotation: glam::Quat,
+    /// Vertical field of view, in degrees
+    pub vertical_fov: f32,
+    pub near_plane: f32,
+    pub far_plane: f32,
+}
+
+impl Camera {
+    pub fn new(
+        position: &QVector3D,
+        rotation: &QVector4D,
+        vertical_fov: f32,
+        near_plane: f32,
+        far_plane: f32,
+    ) -> Self {
+        Self {
+            origin: glam::DVec3::ZERO,
+            position: glam::Vec3::new(position.x(), position.y(), position.z()),
+            rotation: glam::Quat::from_xyzw(rotation.x(), rotation.y(), rotation.z(), rotation.w()),
+            vertical_fov,
+            near_plane,
+            far_plane,
+        }
+    }
+
+    /// Camera placed around the world position `origin`
+    pub fn with_origin(self, origin: glam::DVec3) -> Self {
+        Self { origin, ..self }
+    }
+
+    /// Position in the scene of the world position `world`
+    pub fn to_scene(&self, world: glam::DVec3) -> glam::Vec3 {
+        (world - self.origin).as_vec3()
+    }
+
+    /// World position of the scene position `scene`
+    pub fn to_world(&self, scene: glam::Vec3) -> glam::DVec3 {
+        self.origin + scene.as_dvec3()
+    }
+
+    /// Matrix from the scene to the view
+    pub fn view_matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_rotation_translation(self.rotation, self.position).inverse()
+    }
+
+    /// Matrix from the world to the view, in double precision so that points kilometres away from
+    /// the origin don't jitter once moved in view space
+    pub fn view_dmatrix(&self) -> glam::DMat4 {
+        glam::DMat4::from_rotation_translation(
+            self.rotation.as_dquat(),
+            self.to_world(self.position),
+        )
+        .inverse()
+    }
+
+    pub fn projection_matrix(&self, width: f32, height: f32) -> glam::Mat4 {
+        let fov = self.vertical_fov.to_radians();
+        let aspect_ratio = width / height;
+
+        glam::Mat4::perspective_rh(fov, aspect_ratio, self.near_plane, self.far_plane)
+    }
+
+    /// Project a scene `position` in item coordinates, `None` when it is behind the camera
+    pub fn world_to_screen(
+        &self,
+        position: glam::Vec3,
+        width: f32,
+        height: f32,
+    ) -> Option<glam::Vec2> {
+        let clip = self.projection_matrix(width, height) * self.view_matrix() * position.extend(1.);
+        if clip.w <= 0. {
+            return None;
+        }
+
+        let ndc = clip.truncate() / clip.w;
+        Some(glam::Vec2::new(
+            (ndc.x + 1.) * 0.5 * width,
+            (1. - ndc.y) * 0.5 * height,
+        ))
+    }
+
+    /// Ray from the camera through `point` of an item of `width` and `height`, as a scene origin and
+    /// a direction
+    pub fn screen_ray(
+        &self,
+        point: glam::Vec2,
+        width: f32,
+        height: f32,
+    ) -> (glam::Vec3, glam::Vec3) {
+        let inverse = (self.projection_matrix(width, height) * self.view_matrix()).inverse();
+        let ndc = glam::Vec2::new(2. * point.x / width - 1., 1. - 2. * point.y / height);
+        // Depth goes from 0 on the near plane to 1 on the far plane
+        let near = inverse.project_point3(ndc.extend(0.));
+        let far = inverse.project_point3(ndc.extend(1.));
+
+        (near, (far - near).normalize_or_zero())
+    }
+
+    /// Part of the segment from `start` to `end` in front of the near plane, as interpolation
+    /// factors along the segment. `None` when the segment is entirely behind it.
+    pub fn clip_segment(&self, start: glam::Vec3, end: glam::Vec3) -> Option<(f32, f32)> {
+        let view = self.view_matrix();
+        // The camera looks towards -Z
+        let start_depth = -view.transform_point3(start).z - self.near_plane;
+        let end_depth = -view.transform_point3(end).z - self.near_plane;
+
+        match (start_depth > 0., end_depth > 0.) {
+            (true, true) => Some((0., 1.)),
+            (false, false) => None,
+            (true, false) => Some((0., start_depth / (start_depth - end_depth))),
+            (false, true) => Some((start_depth / (start_depth - end_depth), 1.)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::DVec3;
+
+    use super::*;
+
+    /// Earth-centered position of a point on the ground, where single precision steps are 50 cm
+    const ORIGIN: DVec3 = DVec3::new(4_205_813.25, 163_892.5, 4_776_431.75);
+
+    fn camera() -> Camera {
+        Camera {
+            origin: ORIGIN,
+            position: glam::Vec3::new(0., 0., 10.),
+            rotation: glam::Quat::IDENTITY,
+            vertical_fov: 60.,
+            near_plane: 0.1,
+            far_plane: 100.,
+        }
+    }
+
+    #[test]
+    fn positions_near_the_origin_round_trip_through_the_scene() {
+        let camera = camera();
+        let world = ORIGIN + DVec3::new(1.234567, -0.5, 2.000001);
+
+        let round_trip = camera.to_world(camera.to_scene(world));
+        assert!(
+            round_trip.abs_diff_eq(world, 1e-6),
+            "{round_trip} != {world}"
+        );
+    }
+
+    #[test]
+    fn view_matrix_keeps_millimetres_far_from_the_world_origin() {
+        let view = camera().view_dmatrix();
+
+        let center = view.transform_point3(ORIGIN);
+        assert!(
+            center.abs_diff_eq(DVec3::new(0., 0., -10.), 1e-8),
+            "{center}"
+        );
+        let offset = view.transform_point3(ORIGIN + DVec3::new(0.001, 0., 0.)) - center;
+        assert!(
+            offset.abs_diff_eq(DVec3::new(0.001, 0., 0.), 1e-8),
+            "{offset}"
+        );
+    }
+}
@@ -4,12 +4,29 @@
 use core::pin::Pin;
 
 use cxx_qt::CxxQtType;
-use cxx_qt_lib::{QColor, QPointF, QVariant, QVector3D, QVector4D};
+use cxx_qt_lib::{QColor, QPointF, QString, QVariant, QVector3D, QVector4D};
 use ffi::{
-    GizmoModeOverride, GizmoOrientation, QQuickItemFlag, QQuickItemUpdatePaintNodeData, QSGNode,
-    TransformPivotPoint,
+    GizmoModeOverride, GizmoOrientation, GizmoSnapMode, QQuickItemFlag,
+    QQuickItemUpdatePaintNodeData, QSGNode, RotationFormat, TransformPivotPoint,
 };
 
+use crate::{
+    camera::Camera,
+    gizmo_style::ffi::gizmo_style_from_base,
+    overlay::{OverlayFrame, OverlayMesh},
+};
+
+mod constraints;
+mod handles;
+mod hierarchy;
+mod labels;
+mod numeric_input;
+mod pivot;
+mod rotation_format;
+mod snapping;
+mod style;
+mod targets;
+
 #[cxx_qt::bridge]
 pub mod ffi {
 
@@ -62,6 +79,9 @@ enum QQuickItemFlag {
         include!("cxx-qt-lib/qvariant.h");
         type QVariant = cxx_qt_lib::QVariant;
 
+        include!("cxx-qt-lib/qstring.h");
+        type QString = cxx_qt_lib::QString;
+
         include!(<QtQuick/QQuickItem>);
         type QQuickItem;
     }
@@ -71,6 +91,11 @@ enum QQuickItemFlag {
         type QSGNode;
     }
 
+    unsafe extern "C++" {
+        include!("gizmo_style.h");
+        type GizmoStyleBase = crate::gizmo_style::ffi::GizmoStyleBase;
+    }
+
     #[qenum(Gizmo)]
     /// The point in space around which all rotations are centered.
     enum TransformPivotPoint {
@@ -78,6 +103,12 @@ enum TransformPivotPoint {
         MedianPoint,
         /// Pivot around each target's own origin
         IndividualOrigins,
+        /// Pivot around the center of the bounding box of targets origins
+        BoundingBoxCenter,
+        /// Pivot around the active (last) target
+        ActiveElement,
+        /// Pivot around `pivotPosition`
+        Cursor,
     }
 
     /// Orientation of a gizmo.
@@ -87,6 +118,46 @@ enum GizmoOrientation {
         Global,
         /// Transformation axes are aligned to the last target's orientation.
         Local,
+        /// Transformation axes are aligned to the last target's parent, given by the
+        /// `parentTransform` of the target.
+        Parent,
+        /// Transformation axes are aligned to the camera.
+        View,
+        /// Transformation axes are aligned to `orientationRotation`.
+        Custom,
+    }
+
+    /// How snapping increments are applied.
+    #[qenum(Gizmo)]
+    enum GizmoSnapMode {
+        /// Transformations move by increments from where the drag started.
+        Relative,
+        /// Positions, rotation angles and scales are rounded to absolute multiples of the
+        /// increments.
+        AbsoluteGrid,
+    }
+
+    #[qenum(Gizmo)]
+    /// How rotations are written in `transformUpdated`. Targets accept both quaternions and Euler
+    /// angles whatever the format.
+    enum RotationFormat {
+        /// `quaternion`, or `[x, y, z, w]` for targets giving an array. Only arrays keep double
+        /// precision, `quaternion` is narrowed to `float`.
+        Quaternion,
+        /// Euler angles in degrees as a `vector3d`, rotating about X, then the rotated Y, then the
+        /// rotated Z
+        EulerXYZ,
+        /// Euler angles rotating about X, then the rotated Z, then the rotated Y
+        EulerXZY,
+        /// Euler angles rotating about Y, then the rotated X, then the rotated Z. This is the
+        /// order of QtQuick3D `eulerRotation`.
+        EulerYXZ,
+        /// Euler angles rotating about Y, then the rotated Z, then the rotated X
+        EulerYZX,
+        /// Euler angles rotating about Z, then the rotated X, then the rotated Y
+        EulerZXY,
+        /// Euler angles rotating about Z, then the rotated Y, then the rotated X
+        EulerZYX,
     }
 
     #[qenum(Gizmo)]
@@ -139,10 +210,15 @@ enum GizmoModeOverride {
         #[qproperty(f32, cameraVerticalFoV, rust_name = "camera_vertical_fov")]
         #[qproperty(f32, cameraNearPlane, rust_name = "camera_near_plane")]
         #[qproperty(f32, cameraFarPlane, rust_name = "camera_far_plane")]
+        #[qproperty(QVariant, sceneOrigin, rust_name = "scene_origin")]
         #[qproperty(QVariant, targets)]
+        #[qproperty(RotationFormat, rotationFormat, rust_name = "rotation_format")]
         #[qproperty(GizmoOrientation, orientation)]
+        #[qproperty(QVector4D, orientationRotation, rust_name = "orientation_rotation")]
         #[qproperty(TransformPivotPoint, pivotPoint, rust_name = "pivot_point")]
+        #[qproperty(QVariant, pivotPosition, rust_name = "pivot_position")]
         #[qproperty(bool, snapping)]
+        #[qproperty(GizmoSnapMode, snapMode, rust_name = "snap_mode")]
         #[qproperty(f32, snapAngle, rust_name = "snap_angle")]
         #[qproperty(f32, snapDistance, rust_name = "snap_distance")]
         #[qproperty(f32, snapScale, rust_name = "snap_scale")]
@@ -164,6 +240,78 @@ enum GizmoModeOverride {
         #[qproperty(f32, highlightAlpha, rust_name = "highlight_alpha")]
         #[qproperty(f32, strokeWidth, rust_name = "stroke_width")]
         #[qproperty(f32, gizmoSize, rust_name = "gizmo_size")]
+        #[qproperty(bool, overlayEnabled, rust_name = "overlay_enabled")]
+        #[qproperty(bool, showLabels, rust_name = "show_labels")]
+        #[qproperty(GizmoModeOverride, activeMode, rust_name = "active_mode", READ, NOTIFY)]
+        #[qproperty(
+            GizmoModeOverride,
+            hoveredMode,
+            rust_name = "hovered_mode",
+            READ,
+            NOTIFY
+        )]
+        #[qproperty(
+            QVector3D,
+            translationDelta,
+            rust_name = "translation_delta",
+            READ,
+            NOTIFY
+        )]
+        #[qproperty(
+            QVector3D,
+            totalTranslation,
+            rust_name = "total_translation",
+            READ,
+            NOTIFY
+        )]
+        #[qproperty(QVector3D, rotationAxis, rust_name = "rotation_axis", READ, NOTIFY)]
+        #[qproperty(f32, rotationDelta, rust_name = "rotation_delta", READ, NOTIFY)]
+        #[qproperty(
+            f32,
+            totalRotationAngle,
+            rust_name = "total_rotation_angle",
+            READ,
+            NOTIFY
+        )]
+        #[qproperty(QVector3D, scaleDelta, rust_name = "scale_delta", READ, NOTIFY)]
+        #[qproperty(QVector3D, totalScale, rust_name = "total_scale", READ, NOTIFY)]
+        #[qproperty(bool, cursorFeedback, rust_name = "cursor_feedback")]
+        #[qproperty(i32, translateCursor, rust_name = "translate_cursor")]
+        #[qproperty(i32, rotateCursor, rust_name = "rotate_cursor")]
+        #[qproperty(i32, scaleCursor, rust_name = "scale_cursor")]
+        #[qproperty(i32, dragCursor, rust_name = "drag_cursor")]
+        #[qproperty(bool, modalTransformEnabled, rust_name = "modal_transform_enabled")]
+        #[qproperty(i32, translateKey, rust_name = "translate_key")]
+        #[qproperty(i32, rotateKey, rust_name = "rotate_key")]
+        #[qproperty(i32, scaleKey, rust_name = "scale_key")]
+        #[qproperty(i32, constrainXKey, rust_name = "constrain_x_key")]
+        #[qproperty(i32, constrainYKey, rust_name = "constrain_y_key")]
+        #[qproperty(i32, constrainZKey, rust_name = "constrain_z_key")]
+        #[qproperty(i32, confirmKey, rust_name = "confirm_key")]
+        #[qproperty(i32, cancelKey, rust_name = "cancel_key")]
+        #[qproperty(bool, numericInputEnabled, rust_name = "numeric_input_enabled")]
+        #[qproperty(QString, numericInput, rust_name = "numeric_input", READ, NOTIFY)]
+        #[qproperty(bool, keyboardNudgeEnabled, rust_name = "keyboard_nudge_enabled")]
+        #[qproperty(bool, lockTranslationX, rust_name = "lock_translation_x")]
+        #[qproperty(bool, lockTranslationY, rust_name = "lock_translation_y")]
+        #[qproperty(bool, lockTranslationZ, rust_name = "lock_translation_z")]
+        #[qproperty(QVariant, minPosition, rust_name = "min_position")]
+        #[qproperty(QVariant, maxPosition, rust_name = "max_position")]
+        #[qproperty(QVector3D, minScale, rust_name = "min_scale")]
+        #[qproperty(QVector3D, maxScale, rust_name = "max_scale")]
+        #[qproperty(bool, allowRotationX, rust_name = "allow_rotation_x")]
+        #[qproperty(bool, allowRotationY, rust_name = "allow_rotation_y")]
+        #[qproperty(bool, allowRotationZ, rust_name = "allow_rotation_z")]
+        #[qproperty(QVariant, snapTargets, rust_name = "snap_targets")]
+        #[qproperty(f32, snapRadius, rust_name = "snap_radius")]
+        #[qproperty(QVariant, snappedTo, rust_name = "snapped_to", READ, NOTIFY)]
+        #[qproperty(
+            QVector3D,
+            snappedPosition,
+            rust_name = "snapped_position",
+            READ,
+            NOTIFY
+        )]
         type Gizmo = super::GizmoRust;
 
         #[inherit]
@@ -180,6 +328,55 @@ enum GizmoModeOverride {
         #[rust_name = "is_visible"]
         fn isVisible(self: &Gizmo) -> bool;
 
+        #[inherit]
+        #[rust_name = "set_cursor_shape"]
+        fn setCursorShape(self: Pin<&mut Gizmo>, shape: i32);
+
+        #[inherit]
+        #[rust_name = "reset_cursor_shape"]
+        fn resetCursorShape(self: Pin<&mut Gizmo>);
+
+        #[inherit]
+        #[rust_name = "view_size"]
+        fn viewSize(self: &Gizmo) -> QSizeF;
+
+        #[inherit]
+        #[rust_name = "view_to_item_transform"]
+        fn viewToItemTransform(self: &Gizmo, matrix: &mut [f32]);
+
+        #[inherit]
+        #[rust_name = "device_pixel_ratio"]
+        fn devicePixelRatio(self: &Gizmo) -> f64;
+
+        #[inherit]
+        fn style(self: &Gizmo) -> *mut GizmoStyleBase;
+
+        #[inherit]
+        #[rust_name = "cursor_position"]
+        fn cursorPosition(self: &Gizmo) -> QPointF;
+
+        #[inherit]
+        #[rust_name = "set_modal_interaction"]
+        fn setModalInteraction(self: Pin<&mut Gizmo>, modal: bool);
+
+        #[inherit]
+        #[rust_name = "update_labels"]
+        unsafe fn updateLabels(
+            self: Pin<&mut Gizmo>,
+            node: *mut QSGNode,
+            positions: &[QPointF],
+            texts: &[QString],
+        );
+
+        #[inherit]
+        #[rust_name = "set_geometry_data"]
+        fn setGeometryData(
+            self: Pin<&mut Gizmo>,
+            vertices: &[[f32; 3]],
+            colors: &[[f32; 4]],
+            indices: &[u32],
+        );
+
         #[cxx_override]
         #[cxx_name = "updateInteraction"]
         fn update_interaction(
@@ -194,6 +391,23 @@ fn update_interaction(
         #[cxx_name = "pickPreview"]
         fn pick_preview(self: Pin<&mut Gizmo>, cursor_position: QPointF) -> bool;
 
+        #[cxx_override]
+        #[cxx_name = "handleKeyPress"]
+        fn handle_key_press(
+            self: Pin<&mut Gizmo>,
+            key: i32,
+            modifiers: i32,
+            auto_repeat: bool,
+        ) -> bool;
+
+        #[cxx_override]
+        #[cxx_name = "confirmModalInteraction"]
+        fn confirm_modal_interaction(self: Pin<&mut Gizmo>);
+
+        #[cxx_override]
+        #[cxx_name = "updateGeometryData"]
+        fn update_geometry_data(self: Pin<&mut Gizmo>);
+
         #[cxx_override]
         #[cxx_name = "updatePaintNode"]
         unsafe fn update_paint_node(
@@ -222,24 +436,76 @@ unsafe fn update_paint_node(
         unsafe fn gizmo_update_paint_node(
             old_node: *mut QSGNode,
             vertices: &[[f32; 2]],
-            colors: &[[f32; 4]],
+            colors: &[[u8; 4]],
             indices: &[u32],
         ) -> *mut QSGNode;
 
         fn extract_target_count_from_qvariant(targets: &QVariant) -> usize;
 
+        /// Fill `vector` with `value` given as a `QVector3D` or an array of 3 numbers in double
+        /// precision, leave it as is otherwise
+        fn extract_dvec3_from_qvariant(value: &QVariant, vector: &mut [f64; 3]);
+
+        /// Fill the slices with the `position`, `rotation` and `scale` of `targets`, given either
+        /// as Qt types or as arrays of numbers in double precision
+        ///
+        /// Rotations are quaternions (x, y, z, w), or when `eulers` is set for the target, Euler
+        /// angles in degrees about X, Y and Z in their first three components.
         fn extract_targets_from_qvariant(
             targets: &QVariant,
-            positions: &mut [QVector3D],
-            rotations: &mut [QVector4D],
-            scales: &mut [QVector3D],
+            positions: &mut [[f64; 3]],
+            rotations: &mut [[f64; 4]],
+            eulers: &mut [bool],
+            scales: &mut [[f64; 3]],
         );
 
+        /// Targets with the transforms replaced, keeping their other keys such as
+        /// `parentTransform`. Transforms given as arrays of numbers are written back as such.
+        ///
+        /// Rotations are written as `QQuaternion`, or as Euler angles in their first three
+        /// components when `euler` is set.
         fn transforms_to_qvariant(
-            positions: &[QVector3D],
-            rotations: &[QVector4D],
-            scales: &[QVector3D],
+            targets: &QVariant,
+            positions: &[[f64; 3]],
+            rotations: &[[f64; 4]],
+            euler: bool,
+            scales: &[[f64; 3]],
         ) -> QVariant;
+
+        /// Fill `parents` with the column-major `parentTransform` of `targets`, given as a
+        /// `QMatrix4x4` or as an array of 16 numbers in double precision in row-major order, like
+        /// the arguments of `Qt.matrix4x4`. Identity when the target doesn't have any.
+        fn extract_target_parents_from_qvariant(targets: &QVariant, parents: &mut [[f64; 16]]);
+
+        /// Fill the slices with the `lockTranslationX/Y/Z`, `allowRotationX/Y/Z`, `minPosition`,
+        /// `maxPosition`, `minScale` and `maxScale` of `targets`, unconstrained when the target
+        /// doesn't have any. Positions are read like the target `position`, and the constraints
+        /// are in the space of its `parentTransform` too.
+        fn extract_target_constraints_from_qvariant(
+            targets: &QVariant,
+            locked_translations: &mut [[bool; 3]],
+            allowed_rotations: &mut [[bool; 3]],
+            min_positions: &mut [[f64; 3]],
+            max_positions: &mut [[f64; 3]],
+            min_scales: &mut [QVector3D],
+            max_scales: &mut [QVector3D],
+        );
+
+        fn extract_snap_point_count_from_qvariant(snap_targets: &QVariant) -> usize;
+
+        /// Fill `points` with the world positions of `snap_targets`, and `sources` with the index
+        /// of the snap target each point belongs to
+        ///
+        /// Positions are world positions, given as `QVector3D` or arrays of numbers in double
+        /// precision, and the points of QtQuick3D nodes are offset by `scene_origin`.
+        fn extract_snap_points_from_qvariant(
+            snap_targets: &QVariant,
+            scene_origin: &[f64; 3],
+            points: &mut [[f64; 3]],
+            sources: &mut [usize],
+        );
+
+        fn qvariant_list_at(list: &QVariant, index: usize) -> QVariant;
     }
 
     impl cxx_qt::Initialize for Gizmo {}
@@ -256,6 +522,10 @@ fn from(value: GizmoOrientation) -> Self {
         match value {
             GizmoOrientation::Global => Self::Global,
             GizmoOrientation::Local => Self::Local,
+            // The gizmo is given a single proxy target oriented along the frame, see `pivot` module
+            GizmoOrientation::Parent | GizmoOrientation::View | GizmoOrientation::Custom => {
+                Self::Local
+            }
             _ => {
                 eprintln!("Unknown GizmoOrientation, defaulting to GizmoOrientation::Global");
                 Self::Global
@@ -275,6 +545,10 @@ fn from(value: TransformPivotPoint) -> Self {
         match value {
             TransformPivotPoint::MedianPoint => Self::MedianPoint,
             TransformPivotPoint::IndividualOrigins => Self::IndividualOrigins,
+            // The gizmo is given a single proxy target on the pivot, see `pivot` module
+            TransformPivotPoint::BoundingBoxCenter
+            | TransformPivotPoint::ActiveElement
+            | TransformPivotPoint::Cursor => Self::MedianPoint,
             _ => {
                 eprintln!(
                     "Unknown TransformPivotPoint, defaulting to TransformPivotPoint::MedianPoint"
@@ -285,12 +559,154 @@ fn from(value: TransformPivotPoint) -> Self {
     }
 }
 
+impl Default for GizmoSnapMode {
+    fn default() -> Self {
+        Self::Relative
+    }
+}
+
+impl Default for RotationFormat {
+    fn default() -> Self {
+        Self::Quaternion
+    }
+}
+
 impl Default for GizmoModeOverride {
     fn default() -> Self {
         Self::NoOverride
     }
 }
 
+impl From<transform_gizmo::GizmoMode> for GizmoModeOverride {
+    fn from(value: transform_gizmo::GizmoMode) -> Self {
+        match value {
+            transform_gizmo::GizmoMode::RotateView => Self::RotateView,
+            transform_gizmo::GizmoMode::RotateX => Self::RotateX,
+            transform_gizmo::GizmoMode::RotateY => Self::RotateY,
+            transform_gizmo::GizmoMode::RotateZ => Self::RotateZ,
+            transform_gizmo::GizmoMode::TranslateView => Self::TranslateView,
+            transform_gizmo::GizmoMode::TranslateX => Self::TranslateX,
+            transform_gizmo::GizmoMode::TranslateY => Self::TranslateY,
+            transform_gizmo::GizmoMode::TranslateZ => Self::TranslateZ,
+            transform_gizmo::GizmoMode::TranslateXY => Self::TranslateXY,
+            transform_gizmo::GizmoMode::TranslateXZ => Self::TranslateXZ,
+            transform_gizmo::GizmoMode::TranslateYZ => Self::TranslateYZ,
+            transform_gizmo::GizmoMode::ScaleUniform => Self::ScaleUniform,
+            transform_gizmo::GizmoMode::ScaleX => Self::ScaleX,
+            transform_gizmo::GizmoMode::ScaleY => Self::ScaleY,
+            transform_gizmo::GizmoMode::ScaleZ => Self::ScaleZ,
+            transform_gizmo::GizmoMode::ScaleXY => Self::ScaleXY,
+            transform_gizmo::GizmoMode::ScaleXZ => Self::ScaleXZ,
+            transform_gizmo::GizmoMode::ScaleYZ => Self::ScaleYZ,
+            _ => Self::NoOverride,
+        }
+    }
+}
+
+impl GizmoModeOverride {
+    fn is_translate(self) -> bool {
+        matches!(
+            self,
+            Self::TranslateView
+                | Self::TranslateX
+                | Self::TranslateY
+                | Self::TranslateZ
+                | Self::TranslateXY
+                | Self::TranslateXZ
+                | Self::TranslateYZ
+        )
+    }
+
+    fn is_rotate(self) -> bool {
+        matches!(
+            self,
+            Self::RotateView | Self::RotateX | Self::RotateY | Self::RotateZ
+        )
+    }
+
+    fn is_scale(self) -> bool {
+        matches!(
+            self,
+            Self::ScaleUniform
+                | Self::ScaleX
+                | Self::ScaleY
+                | Self::ScaleZ
+                | Self::ScaleXY
+                | Self::ScaleXZ
+                | Self::ScaleYZ
+        )
+    }
+
+    /// Axes affected by the mode, in gizmo space
+    ///
+    /// View modes are not bound to the gizmo axes: free translations and uniform scaling affect
+    /// all of them, view rotations none.
+    fn axes(self) -> glam::DVec3 {
+        match self {
+            Self::TranslateX | Self::RotateX | Self::ScaleX => glam::DVec3::X,
+            Self::TranslateY | Self::RotateY | Self::ScaleY => glam::DVec3::Y,
+            Self::TranslateZ | Self::RotateZ | Self::ScaleZ => glam::DVec3::Z,
+            Self::TranslateXY | Self::ScaleXY => glam::DVec3::new(1., 1., 0.),
+            Self::TranslateXZ | Self::ScaleXZ => glam::DVec3::new(1., 0., 1.),
+            Self::TranslateYZ | Self::ScaleYZ => glam::DVec3::new(0., 1., 1.),
+            Self::TranslateView | Self::ScaleUniform => glam::DVec3::ONE,
+            _ => glam::DVec3::ZERO,
+        }
+    }
+}
+
+impl GizmoModeOverride {
+    /// Mode of the same kind without any axis constraint
+    fn unconstrained(self) -> Self {
+        if self.is_translate() {
+            Self::TranslateView
+        } else if self.is_rotate() {
+            Self::RotateView
+        } else if self.is_scale() {
+            Self::ScaleUniform
+        } else {
+            self
+        }
+    }
+
+    /// Mode of the same kind constrained to `axis` (0 for X, 1 for Y, 2 for Z), or to the plane
+    /// orthogonal to `axis` when `plane` is set.
+    ///
+    /// Constraining twice to the same axis removes the constraint.
+    fn constrained(self, axis: usize, plane: bool) -> Self {
+        let modes = if self.is_translate() {
+            [
+                (Self::TranslateX, Self::TranslateYZ),
+                (Self::TranslateY, Self::TranslateXZ),
+                (Self::TranslateZ, Self::TranslateXY),
+            ]
+        } else if self.is_rotate() {
+            // Rotations can't be constrained to a plane
+            [
+                (Self::RotateX, Self::RotateX),
+                (Self::RotateY, Self::RotateY),
+                (Self::RotateZ, Self::RotateZ),
+            ]
+        } else if self.is_scale() {
+            [
+                (Self::ScaleX, Self::ScaleYZ),
+                (Self::ScaleY, Self::ScaleXZ),
+                (Self::ScaleZ, Self::ScaleXY),
+            ]
+        } else {
+            return self;
+        };
+
+        let (axis_mode, plane_mode) = modes[axis];
+        let mode = if plane { plane_mode } else { axis_mode };
+        if mode == self {
+            self.unconstrained()
+        } else {
+            mode
+        }
+    }
+}
+
 impl From<GizmoModeOverride> for Option<transform_gizmo::GizmoMode> {
     fn from(value: GizmoModeOverride) -> Self {
         match value {
@@ -317,6 +733,94 @@ fn from(value: GizmoModeOverride) -> Self {
     }
 }
 
+// Values of `Qt::CursorShape`
+const QT_CROSS_CURSOR: i32 = 2;
+const QT_SIZE_F_DIAG_CURSOR: i32 = 8;
+const QT_SIZE_ALL_CURSOR: i32 = 9;
+const QT_CLOSED_HAND_CURSOR: i32 = 18;
+
+// Values of `Qt::Key`
+const QT_KEY_ESCAPE: i32 = 0x0100_0000;
+const QT_KEY_BACKSPACE: i32 = 0x0100_0003;
+const QT_KEY_RETURN: i32 = 0x0100_0004;
+const QT_KEY_ENTER: i32 = 0x0100_0005;
+const QT_KEY_G: i32 = 0x47;
+const QT_KEY_R: i32 = 0x52;
+const QT_KEY_S: i32 = 0x53;
+const QT_KEY_X: i32 = 0x58;
+const QT_KEY_Y: i32 = 0x59;
+const QT_KEY_Z: i32 = 0x5a;
+
+const QT_KEY_LEFT: i32 = 0x0100_0012;
+const QT_KEY_UP: i32 = 0x0100_0013;
+const QT_KEY_RIGHT: i32 = 0x0100_0014;
+const QT_KEY_DOWN: i32 = 0x0100_0015;
+const QT_KEY_PAGE_UP: i32 = 0x0100_0016;
+const QT_KEY_PAGE_DOWN: i32 = 0x0100_0017;
+
+// Values of `Qt::KeyboardModifier`
+const QT_SHIFT_MODIFIER: i32 = 0x0200_0000;
+const QT_CONTROL_MODIFIER: i32 = 0x0400_0000;
+
+/// Axis (0 for X, 1 for Y, 2 for Z) and direction nudged by `key`
+///
+/// Left/Right move along X, PageDown/PageUp along Y and Up/Down along Z, Up moving away from a
+/// Y-up camera looking down -Z.
+fn nudge_axis(key: i32) -> Option<(usize, f64)> {
+    match key {
+        QT_KEY_LEFT => Some((0, -1.)),
+        QT_KEY_RIGHT => Some((0, 1.)),
+        QT_KEY_PAGE_DOWN => Some((1, -1.)),
+        QT_KEY_PAGE_UP => Some((1, 1.)),
+        QT_KEY_UP => Some((2, -1.)),
+        QT_KEY_DOWN => Some((2, 1.)),
+        _ => None,
+    }
+}
+
+/// Single-mode gizmo of `mode` in `gizmos`, configured like `config` and created when missing
+fn mode_gizmo(
+    gizmos: &mut Vec<(transform_gizmo::GizmoMode, transform_gizmo::Gizmo)>,
+    mode: transform_gizmo::GizmoMode,
+    config: transform_gizmo::GizmoConfig,
+) -> &mut transform_gizmo::Gizmo {
+    let config = transform_gizmo::GizmoConfig {
+        modes: transform_gizmo::EnumSet::only(mode),
+        mode_override: None,
+        ..config
+    };
+    match gizmos
+        .iter()
+        .position(|(gizmo_mode, _)| *gizmo_mode == mode)
+    {
+        Some(index) => {
+            let gizmo = &mut gizmos[index].1;
+            gizmo.update_config(config);
+            gizmo
+        }
+        None => {
+            gizmos.push((mode, transform_gizmo::Gizmo::new(config)));
+            &mut gizmos.last_mut().unwrap().1
+        }
+    }
+}
+
+/// Vector of `value`, given as a `vector3d` or an array of 3 numbers, `default` when it is neither
+fn dvec3_or(value: &QVariant, default: glam::DVec3) -> glam::DVec3 {
+    let mut vector = default.to_array();
+    ffi::extract_dvec3_from_qvariant(value, &mut vector);
+    glam::DVec3::from_array(vector)
+}
+
+/// Mean of the `targets` origins
+fn median_point(targets: &[transform_gizmo::math::Transform]) -> glam::DVec3 {
+    targets
+        .iter()
+        .map(|target| glam::DVec3::from(target.translation))
+        .sum::<glam::DVec3>()
+        / targets.len().max(1) as f64
+}
+
 #[derive(Default)]
 pub struct GizmoRust {
     camera_position: QVector3D,
@@ -324,24 +828,42 @@ pub struct GizmoRust {
     camera_vertical_fov: f32,
     camera_near_plane: f32,
     camera_far_plane: f32,
+    /// World position of the QtQuick3D scene origin, which the camera and the scene nodes are
+    /// relative to, as a `vector3d` or an array of numbers in double precision. Zero when unset.
+    scene_origin: QVariant,
     targets: QVariant,
+    /// `targets` parsed when it or `rotation_format` changes
+    parsed_targets: targets::Targets,
+    rotation_format: RotationFormat,
     gizmo: Option<transform_gizmo::Gizmo>,
     gizmo_updated_since_last_draw: bool,
+    /// Single-mode gizmos of each handle, picking the hovered one and drawing the geometry placed
+    /// in the world
+    mode_gizmos: Vec<(transform_gizmo::GizmoMode, transform_gizmo::Gizmo)>,
+    /// Frame shown by the overlay node
+    overlay_frame: OverlayFrame,
     /// Keep last interaction in case the target moves while we are dragging
     gizmo_last_interaction: Option<transform_gizmo::GizmoInteraction>,
     /// Determines the gizmo's orientation relative to global or local axes.
     orientation: GizmoOrientation,
+    /// Orientation of the `Custom` frame, as a quaternion with the scalar part in `w`
+    orientation_rotation: QVector4D,
     /// Pivot point for transformations
     pivot_point: TransformPivotPoint,
+    /// World position of the 3D cursor, used by the `Cursor` pivot point, as a `vector3d` or an
+    /// array of numbers in double precision
+    pivot_position: QVariant,
     /// Toggles snapping to predefined increments during transformations for precision.
     snapping: bool,
+    /// Snap relatively to the drag start or to an absolute grid.
+    snap_mode: GizmoSnapMode,
     /// Angle increment for snapping rotations, in radians.
     snap_angle: f32,
     /// Distance increment for snapping translations.
     snap_distance: f32,
     /// Scale increment for snapping scalings.
     snap_scale: f32,
-    /// Ratio of window's physical size to logical size.
+    /// Ratio of window's physical size to logical size, 0 to follow the window device pixel ratio.
     pixels_per_point: f32,
     translate_enabled: bool,
     translate_view_enabled: bool,
@@ -364,32 +886,298 @@ pub struct GizmoRust {
     stroke_width: f32,
     /// Gizmo size in pixels
     gizmo_size: f32,
+    /// Draw the gizmo on top of the item. Disable it when the gizmo is only rendered in the scene
+    /// by a `GizmoGeometry`.
+    overlay_enabled: bool,
+    /// Draw the angle swept by rotations and the distance of translations while dragging, over
+    /// the item only
+    show_labels: bool,
+    /// Mode being manipulated by the current drag, `NoOverride` when idle
+    active_mode: GizmoModeOverride,
+    /// Mode under the cursor, `NoOverride` when nothing is hovered
+    hovered_mode: GizmoModeOverride,
+    /// Translation applied by the last drag update
+    translation_delta: QVector3D,
+    /// Translation applied since the drag started
+    total_translation: QVector3D,
+    /// Axis of the current rotation
+    rotation_axis: QVector3D,
+    /// Rotation angle applied by the last drag update, in radians.
+    rotation_delta: f32,
+    /// Rotation angle applied since the drag started, in radians.
+    total_rotation_angle: f32,
+    /// Scale factor applied by the last drag update
+    scale_delta: QVector3D,
+    /// Scale factor applied since the drag started
+    total_scale: QVector3D,
+    /// Change the cursor shape when hovering or dragging the gizmo
+    cursor_feedback: bool,
+    /// `Qt::CursorShape` used when hovering a translation handle
+    translate_cursor: i32,
+    /// `Qt::CursorShape` used when hovering a rotation handle
+    rotate_cursor: i32,
+    /// `Qt::CursorShape` used when hovering a scale handle
+    scale_cursor: i32,
+    /// `Qt::CursorShape` used while dragging any handle
+    drag_cursor: i32,
+    /// Allow starting transforms from the keyboard without grabbing a handle
+    modal_transform_enabled: bool,
+    /// `Qt::Key` starting a modal translation
+    translate_key: i32,
+    /// `Qt::Key` starting a modal rotation
+    rotate_key: i32,
+    /// `Qt::Key` starting a modal scaling
+    scale_key: i32,
+    /// `Qt::Key` constraining the modal transform to the X axis, or the YZ plane with Shift
+    constrain_x_key: i32,
+    /// `Qt::Key` constraining the modal transform to the Y axis, or the XZ plane with Shift
+    constrain_y_key: i32,
+    /// `Qt::Key` constraining the modal transform to the Z axis, or the XY plane with Shift
+    constrain_z_key: i32,
+    /// `Qt::Key` applying the modal transform, along with keypad Enter when it is Return
+    confirm_key: i32,
+    /// `Qt::Key` reverting the modal transform
+    cancel_key: i32,
+    /// Mode forced by the modal transform, `NoOverride` when no modal transform is running
+    modal_mode: GizmoModeOverride,
+    /// Targets before the modal transform started, restored on cancel
+    modal_start_targets: QVariant,
+    /// Allow typing exact values while dragging
+    numeric_input_enabled: bool,
+    /// Value typed during the current drag
+    numeric_input: QString,
+    /// Move, rotate (Ctrl) and scale (Shift) targets by snap increments with arrow keys and
+    /// PageUp/PageDown
+    keyboard_nudge_enabled: bool,
+    /// Manipulation done with the pointer when typing started
+    numeric_pointer_manipulation: numeric_input::PointerManipulation,
+    /// Cursor position when the current drag started, in view coordinates
+    drag_start_cursor: glam::Vec2,
+    /// Targets when the current drag started
+    drag_start_transforms: Vec<transform_gizmo::math::Transform>,
+    /// Targets when the current drag started, in their parent space
+    drag_start_local_transforms: Vec<transform_gizmo::math::Transform>,
+    /// Keep the world X coordinate of targets
+    lock_translation_x: bool,
+    /// Keep the world Y coordinate of targets
+    lock_translation_y: bool,
+    /// Keep the world Z coordinate of targets
+    lock_translation_z: bool,
+    /// Lower corner of the world box targets origins must stay in, as a `vector3d` or an array of
+    /// numbers in double precision. Unbounded when unset.
+    min_position: QVariant,
+    /// Upper corner of the world box targets origins must stay in, as a `vector3d` or an array of
+    /// numbers in double precision. Unbounded when unset.
+    max_position: QVariant,
+    /// Minimum scale of targets, per axis
+    min_scale: QVector3D,
+    /// Maximum scale of targets, per axis
+    max_scale: QVector3D,
+    /// Allow rotations around the gizmo X axis
+    allow_rotation_x: bool,
+    /// Allow rotations around the gizmo Y axis
+    allow_rotation_y: bool,
+    /// Allow rotations around the gizmo Z axis
+    allow_rotation_z: bool,
+    /// Positions or QtQuick3D nodes translations snap to. The nodes being transformed should not
+    /// be part of them, or the targets would snap to themselves.
+    snap_targets: QVariant,
+    /// World positions of `snap_targets`, with the index of the snap target each belongs to,
+    /// parsed when it changes
+    snap_points: Vec<(glam::DVec3, usize)>,
+    /// Distance in pixels from the cursor under which a snap target is picked
+    snap_radius: f32,
+    /// Entry of `snap_targets` the current translation snapped to, null when not snapped
+    snapped_to: QVariant,
+    /// Position in the QtQuick3D scene, relative to `scene_origin`, the current translation snapped
+    /// to
+    snapped_position: QVector3D,
+    /// Index in `snap_targets` of `snapped_to`
+    snapped_index: Option<usize>,
 }
 
 impl GizmoRust {
+    fn camera(&self) -> Camera {
+        Camera::new(
+            &self.camera_position,
+            &self.camera_rotation,
+            self.camera_vertical_fov,
+            self.camera_near_plane,
+            self.camera_far_plane,
+        )
+        .with_origin(dvec3_or(&self.scene_origin, glam::DVec3::ZERO))
+    }
+
     fn view_matrix(&self) -> glam::Mat4 {
-        let rotation = glam::Quat::from_xyzw(
+        self.camera().view_matrix()
+    }
+
+    /// Prepare exact transforms of `targets` in `mode`
+    fn numeric_transform<'a>(
+        &self,
+        mode: GizmoModeOverride,
+        targets: &'a [transform_gizmo::math::Transform],
+        pointer: numeric_input::PointerManipulation,
+    ) -> numeric_input::NumericTransform<'a> {
+        let camera_rotation = glam::Quat::from_xyzw(
             self.camera_rotation.x(),
             self.camera_rotation.y(),
             self.camera_rotation.z(),
             self.camera_rotation.w(),
+        )
+        .as_dquat();
+
+        numeric_input::NumericTransform {
+            mode,
+            frame: self.frame(targets),
+            view_axis: camera_rotation * glam::DVec3::NEG_Z,
+            pivot: self.pivot(targets),
+            pointer,
+            targets,
+        }
+    }
+
+    /// Orientation of the gizmo axes for `targets`
+    fn frame(&self, targets: &[transform_gizmo::math::Transform]) -> glam::DQuat {
+        match (self.orientation, targets.last()) {
+            (GizmoOrientation::Local, Some(target)) => target.rotation.into(),
+            (GizmoOrientation::Parent, Some(_)) => {
+                self.target_parents()
+                    .last()
+                    .map_or(glam::DQuat::IDENTITY, |parent| {
+                        let (_, rotation, _) = parent.to_scale_rotation_translation();
+                        rotation.normalize()
+                    })
+            }
+            (GizmoOrientation::View, _) => glam::Quat::from_xyzw(
+                self.camera_rotation.x(),
+                self.camera_rotation.y(),
+                self.camera_rotation.z(),
+                self.camera_rotation.w(),
+            )
+            .as_dquat()
+            .normalize(),
+            (GizmoOrientation::Custom, _) => glam::Quat::from_xyzw(
+                self.orientation_rotation.x(),
+                self.orientation_rotation.y(),
+                self.orientation_rotation.z(),
+                self.orientation_rotation.w(),
+            )
+            .as_dquat()
+            .try_normalize()
+            .unwrap_or(glam::DQuat::IDENTITY),
+            _ => glam::DQuat::IDENTITY,
+        }
+    }
+
+    /// World transforms of the parents of the targets
+    fn target_parents(&self) -> &[glam::DMat4] {
+        self.parsed_targets.parents()
+    }
+
+    /// Constraints of each target, the gizmo ones in world space along with the target allowed
+    /// rotations, and the other target ones in its parent space
+    fn target_constraints(&self) -> Vec<(constraints::Constraints, constraints::Constraints)> {
+        let to_dvec3 = |v: &QVector3D| glam::Vec3::new(v.x(), v.y(), v.z()).as_dvec3();
+        let constraints = constraints::Constraints {
+            locked_translation: glam::BVec3::new(
+                self.lock_translation_x,
+                self.lock_translation_y,
+                self.lock_translation_z,
+            ),
+            allowed_rotation: glam::BVec3::new(
+                self.allow_rotation_x,
+                self.allow_rotation_y,
+                self.allow_rotation_z,
+            ),
+            min_position: dvec3_or(&self.min_position, glam::DVec3::NEG_INFINITY),
+            max_position: dvec3_or(&self.max_position, glam::DVec3::INFINITY),
+            min_scale: to_dvec3(&self.min_scale),
+            max_scale: to_dvec3(&self.max_scale),
+        };
+
+        self.parsed_targets
+            .constraints()
+            .iter()
+            .map(|target| {
+                let world = constraints.intersect(&constraints::Constraints {
+                    allowed_rotation: target.allowed_rotation,
+                    ..Default::default()
+                });
+                let local = constraints::Constraints {
+                    allowed_rotation: glam::BVec3::TRUE,
+                    ..*target
+                };
+                (world, local)
+            })
+            .collect()
+    }
+
+    /// Common pivot of `targets`, `None` when each target pivots around its own origin
+    fn pivot(&self, targets: &[transform_gizmo::math::Transform]) -> Option<glam::DVec3> {
+        match self.pivot_point {
+            TransformPivotPoint::IndividualOrigins => None,
+            TransformPivotPoint::MedianPoint => Some(median_point(targets)),
+            _ => self.custom_pivot(targets),
+        }
+    }
+
+    /// World position the gizmo is drawn at
+    fn center(&self, targets: &[transform_gizmo::math::Transform]) -> glam::DVec3 {
+        self.custom_pivot(targets)
+            .unwrap_or_else(|| median_point(targets))
+    }
+
+    /// Target handed to `transform_gizmo` in place of `targets`, when the pivot or the orientation
+    /// is not supported by the gizmo
+    fn proxy(
+        &self,
+        targets: &[transform_gizmo::math::Transform],
+    ) -> Option<transform_gizmo::math::Transform> {
+        let custom_orientation = matches!(
+            self.orientation,
+            GizmoOrientation::Parent | GizmoOrientation::View | GizmoOrientation::Custom
         );
-        let translation = glam::Vec3::new(
-            self.camera_position.x(),
-            self.camera_position.y(),
-            self.camera_position.z(),
-        );
+        let custom_pivot = self.custom_pivot(targets);
+        if targets.is_empty() || (!custom_orientation && custom_pivot.is_none()) {
+            return None;
+        }
 
-        glam::Mat4::from_rotation_translation(rotation, translation).inverse()
+        let position = custom_pivot.unwrap_or_else(|| median_point(targets));
+        Some(pivot::proxy(position, self.frame(targets)))
     }
 
-    fn projection_matrix(&self, width: f32, height: f32) -> glam::Mat4 {
-        let fov = self.camera_vertical_fov.to_radians();
-        let aspect_ratio = width / height;
-        let near = self.camera_near_plane;
-        let far = self.camera_far_plane;
+    /// Single target placed and oriented like the gizmo drawn for `targets`
+    fn placement(
+        &self,
+        targets: &[transform_gizmo::math::Transform],
+    ) -> transform_gizmo::math::Transform {
+        self.proxy(targets)
+            .unwrap_or_else(|| pivot::proxy(median_point(targets), self.frame(targets)))
+    }
 
-        glam::Mat4::perspective_rh(fov, aspect_ratio, near, far)
+    /// Pivot `transform_gizmo` can't compute by itself
+    fn custom_pivot(&self, targets: &[transform_gizmo::math::Transform]) -> Option<glam::DVec3> {
+        let positions = targets
+            .iter()
+            .map(|target| glam::DVec3::from(target.translation));
+
+        match self.pivot_point {
+            TransformPivotPoint::BoundingBoxCenter => {
+                let (min, max) = positions.fold(
+                    (glam::DVec3::INFINITY, glam::DVec3::NEG_INFINITY),
+                    |(min, max), position| (min.min(position), max.max(position)),
+                );
+                (!targets.is_empty()).then(|| (min + max) * 0.5)
+            }
+            TransformPivotPoint::ActiveElement => positions.last(),
+            TransformPivotPoint::Cursor => Some(dvec3_or(&self.pivot_position, glam::DVec3::ZERO)),
+            _ => None,
+        }
+    }
+
+    fn projection_matrix(&self, width: f32, height: f32) -> glam::Mat4 {
+        self.camera().projection_matrix(width, height)
     }
 }
 
@@ -400,7 +1188,8 @@ fn initialize(mut self: Pin<&mut Self>) {
             this.snap_angle = transform_gizmo::config::DEFAULT_SNAP_ANGLE;
             this.snap_distance = transform_gizmo::config::DEFAULT_SNAP_DISTANCE;
             this.snap_scale = transform_gizmo::config::DEFAULT_SNAP_SCALE;
-            this.pixels_per_point = 1.;
+            this.pixels_per_point = 0.;
+            this.orientation_rotation = QVector4D::new(0., 0., 0., 1.);
 
             this.translate_enabled = true;
             this.translate_plane_enabled = true;
@@ -420,6 +1209,38 @@ fn initialize(mut self: Pin<&mut Self>) {
             this.highlight_alpha = 1.0;
             this.stroke_width = 4.0;
             this.gizmo_size = 75.0;
+            this.overlay_enabled = true;
+            this.show_labels = true;
+
+            this.scale_delta = QVector3D::new(1., 1., 1.);
+            this.total_scale = QVector3D::new(1., 1., 1.);
+
+            this.cursor_feedback = true;
+            this.translate_cursor = QT_SIZE_ALL_CURSOR;
+            this.rotate_cursor = QT_CROSS_CURSOR;
+            this.scale_cursor = QT_SIZE_F_DIAG_CURSOR;
+            this.drag_cursor = QT_CLOSED_HAND_CURSOR;
+
+            this.modal_transform_enabled = true;
+            this.translate_key = QT_KEY_G;
+            this.rotate_key = QT_KEY_R;
+            this.scale_key = QT_KEY_S;
+            this.constrain_x_key = QT_KEY_X;
+            this.constrain_y_key = QT_KEY_Y;
+            this.constrain_z_key = QT_KEY_Z;
+            this.confirm_key = QT_KEY_RETURN;
+            this.cancel_key = QT_KEY_ESCAPE;
+
+            this.numeric_input_enabled = true;
+            this.keyboard_nudge_enabled = true;
+
+            this.snap_radius = 10.;
+
+            this.min_scale = QVector3D::new(-f32::MAX, -f32::MAX, -f32::MAX);
+            this.max_scale = QVector3D::new(f32::MAX, f32::MAX, f32::MAX);
+            this.allow_rotation_x = true;
+            this.allow_rotation_y = true;
+            this.allow_rotation_z = true;
         }
 
         self.as_mut()
@@ -440,6 +1261,13 @@ fn initialize(mut self: Pin<&mut Self>) {
         self.as_mut()
             .on_camera_rotation_changed(|qobject| qobject.update())
             .release();
+        self.as_mut()
+            .on_scene_origin_changed(|mut qobject| {
+                // Snap points of the scene nodes are offset by the origin
+                qobject.as_mut().parse_snap_targets();
+                qobject.update();
+            })
+            .release();
 
         self.as_mut()
             .on_translate_enabled_changed(|qobject| qobject.update())
@@ -475,6 +1303,15 @@ fn initialize(mut self: Pin<&mut Self>) {
         self.as_mut()
             .on_orientation_changed(|qobject| qobject.update())
             .release();
+        self.as_mut()
+            .on_orientation_rotation_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_pivot_point_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_pivot_position_changed(|qobject| qobject.update())
+            .release();
 
         self.as_mut()
             .on_x_color_changed(|qobject| qobject.update())
@@ -501,9 +1338,75 @@ fn initialize(mut self: Pin<&mut Self>) {
         self.as_mut()
             .on_gizmo_size_changed(|qobject| qobject.update())
             .release();
+        self.as_mut()
+            .on_overlay_enabled_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_show_labels_changed(|qobject| qobject.update())
+            .release();
 
         self.as_mut()
-            .on_targets_changed(|qobject| qobject.update())
+            .on_targets_changed(|mut qobject| {
+                qobject.as_mut().parse_targets();
+                qobject.update();
+            })
+            .release();
+        self.as_mut()
+            .on_snap_targets_changed(|qobject| qobject.parse_snap_targets())
+            .release();
+        // Euler angles of the targets are read in the new order
+        self.as_mut()
+            .on_rotation_format_changed(|mut qobject| {
+                qobject.as_mut().parse_targets();
+                qobject.update();
+            })
+            .release();
+
+        self.as_mut()
+            .on_lock_translation_x_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_lock_translation_y_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_lock_translation_z_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_min_position_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_max_position_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_min_scale_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_max_scale_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_allow_rotation_x_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_allow_rotation_y_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_allow_rotation_z_changed(|qobject| qobject.update())
+            .release();
+
+        self.as_mut()
+            .on_cursor_feedback_changed(|qobject| qobject.update_cursor())
+            .release();
+        self.as_mut()
+            .on_translate_cursor_changed(|qobject| qobject.update_cursor())
+            .release();
+        self.as_mut()
+            .on_rotate_cursor_changed(|qobject| qobject.update_cursor())
+            .release();
+        self.as_mut()
+            .on_scale_cursor_changed(|qobject| qobject.update_cursor())
+            .release();
+        self.as_mut()
+            .on_drag_cursor_changed(|qobject| qobject.update_cursor())
             .release();
     }
 }
@@ -518,38 +1421,70 @@ fn update_interaction(
     ) {
         self.as_mut().update();
         let interaction = transform_gizmo::GizmoInteraction {
-            cursor_pos: (cursor_position.x() as f32, cursor_position.y() as f32),
+            cursor_pos: self.to_gizmo_position(&cursor_position),
             hovered,
             drag_started,
             dragging,
         };
-        let result = self.as_mut().update_interaction_impl(interaction);
-        if let Some((_, transforms)) = result {
-            let (positions, rotations, scales): (Vec<QVector3D>, Vec<QVector4D>, Vec<QVector3D>) =
-                itertools::multiunzip(transforms.iter().map(|transform| {
-                    let position = QVector3D::new(
-                        transform.translation.x as f32,
-                        transform.translation.y as f32,
-                        transform.translation.z as f32,
-                    );
-                    let rotation = QVector4D::new(
-                        transform.rotation.v.x as f32,
-                        transform.rotation.v.y as f32,
-                        transform.rotation.v.z as f32,
-                        transform.rotation.s as f32,
-                    );
-                    let scale = QVector3D::new(
-                        transform.scale.x as f32,
-                        transform.scale.y as f32,
-                        transform.scale.z as f32,
-                    );
-
-                    (position, rotation, scale)
-                }));
-            let transforms = ffi::transforms_to_qvariant(&positions, &rotations, &scales);
 
-            self.as_mut().rust_mut().targets = transforms.clone();
-            self.as_mut().transform_updated(transforms);
+        if !dragging {
+            self.as_mut().set_active_mode(GizmoModeOverride::NoOverride);
+            self.as_mut().set_numeric_input(String::new());
+            self.as_mut().rust_mut().drag_start_transforms.clear();
+            self.as_mut().rust_mut().drag_start_local_transforms.clear();
+            self.as_mut().set_snapped(None);
+        } else if drag_started {
+            let modal_mode = self.rust().modal_mode;
+            // Touch and pen drags start without any hover state
+            let active_mode = if modal_mode != GizmoModeOverride::NoOverride {
+                modal_mode
+            } else {
+                let _ = self
+                    .as_mut()
+                    .update_interaction_impl(transform_gizmo::GizmoInteraction {
+                        hovered: true,
+                        drag_started: false,
+                        dragging: false,
+                        ..interaction
+                    });
+                self.as_mut()
+                    .focused_mode(interaction.cursor_pos)
+                    .map_or(GizmoModeOverride::NoOverride, GizmoModeOverride::from)
+            };
+            self.as_mut().set_active_mode(active_mode);
+            self.as_mut().reset_readouts();
+            self.as_mut().set_numeric_input(String::new());
+            let transforms = self.target_transforms().to_vec();
+            let local_transforms = self.local_target_transforms().to_vec();
+            let mut this = self.as_mut().rust_mut();
+            this.drag_start_cursor =
+                glam::Vec2::new(cursor_position.x() as f32, cursor_position.y() as f32);
+            this.drag_start_transforms = transforms;
+            this.drag_start_local_transforms = local_transforms;
+        }
+        let result = self.as_mut().update_interaction_impl(interaction);
+        if !dragging {
+            // Read after the update, which focuses the hovered handle
+            let hovered_mode = if hovered {
+                self.as_mut().focused_mode(interaction.cursor_pos)
+            } else {
+                None
+            };
+            self.as_mut().set_hovered_mode(
+                hovered_mode.map_or(GizmoModeOverride::NoOverride, GizmoModeOverride::from),
+            );
+        }
+        self.as_mut().update_cursor();
+
+        // A typed value takes precedence over the pointer
+        let typing = !self.rust().numeric_input.is_empty();
+        if let Some((result, transforms)) = result.filter(|_| !typing) {
+            let transforms = self.as_ref().snap_to_grid(&result, transforms);
+            let transforms = self
+                .as_mut()
+                .snap_to_points(&result, transforms, &cursor_position);
+            self.as_mut().update_readouts(&result);
+            self.as_mut().emit_transforms(&transforms);
         }
         self.as_mut().rust_mut().gizmo_last_interaction = Some(transform_gizmo::GizmoInteraction {
             drag_started: false,
@@ -557,6 +1492,171 @@ fn update_interaction(
         });
     }
 
+    /// Store `transforms`, in world space, as the new targets and notify QML
+    fn emit_transforms(mut self: Pin<&mut Self>, transforms: &[transform_gizmo::math::Transform]) {
+        // Constrain the targets and bring them back to their parent space
+        let transforms: Vec<_> = {
+            let this = self.rust();
+            let (start, start_local) = if this.drag_start_transforms.is_empty() {
+                (self.target_transforms(), self.local_target_transforms())
+            } else {
+                (
+                    this.drag_start_transforms.as_slice(),
+                    this.drag_start_local_transforms.as_slice(),
+                )
+            };
+            let frame = this.frame(start);
+            itertools::multizip((
+                transforms,
+                this.target_constraints(),
+                this.target_parents(),
+                start,
+                start_local,
+            ))
+            .map(|(transform, (world, local), parent, start, start_local)| {
+                let transform = world.apply(transform, start, frame);
+                let transform = hierarchy::to_local(*parent, &transform, start, start_local);
+                // Like its position, the target constraints are in its parent space
+                local.apply(&transform, start_local, glam::DQuat::IDENTITY)
+            })
+            .collect()
+        };
+
+        let euler_order = self.rust().rotation_format.euler_order();
+        let (positions, rotations, scales): (Vec<_>, Vec<_>, Vec<_>) =
+            itertools::multiunzip(transforms.iter().map(|transform| {
+                let position = glam::DVec3::from(transform.translation).to_array();
+                let rotation = glam::DQuat::from(transform.rotation);
+                let rotation = match euler_order {
+                    Some(order) => {
+                        let [x, y, z] = rotation_format::to_euler(order, rotation);
+                        [x, y, z, 0.]
+                    }
+                    None => rotation.to_array(),
+                };
+                let scale = glam::DVec3::from(transform.scale).to_array();
+
+                (position, rotation, scale)
+            }));
+        let qtransforms = ffi::transforms_to_qvariant(
+            self.targets(),
+            &positions,
+            &rotations,
+            euler_order.is_some(),
+            &scales,
+        );
+
+        {
+            let mut this = self.as_mut().rust_mut();
+            this.targets = qtransforms.clone();
+            // Setting the field doesn't go through the change handler
+            this.parsed_targets.set_local(transforms);
+        }
+        self.as_mut().transform_updated(qtransforms);
+    }
+
+    fn snap_to_grid(
+        &self,
+        result: &transform_gizmo::GizmoResult,
+        transforms: Vec<transform_gizmo::math::Transform>,
+    ) -> Vec<transform_gizmo::math::Transform> {
+        let this = self.rust();
+        if !this.snapping || this.snap_mode != GizmoSnapMode::AbsoluteGrid {
+            return transforms;
+        }
+
+        let config = self.gizmo_config();
+        let start = &this.drag_start_transforms;
+        snapping::AbsoluteGrid {
+            distance: config.snap_distance as f64,
+            angle: config.snap_angle as f64,
+            scale: config.snap_scale as f64,
+            frame: this.frame(start),
+            axes: this.active_mode.axes(),
+            pivot: this.pivot(start),
+        }
+        .snap(result, start, &transforms)
+    }
+
+    /// Snap translations onto the `snap_targets` closest to the cursor
+    fn snap_to_points(
+        mut self: Pin<&mut Self>,
+        result: &transform_gizmo::GizmoResult,
+        transforms: Vec<transform_gizmo::math::Transform>,
+        cursor_position: &QPointF,
+    ) -> Vec<transform_gizmo::math::Transform> {
+        if !matches!(result, transform_gizmo::GizmoResult::Translation { .. }) {
+            self.set_snapped(None);
+            return transforms;
+        }
+
+        let size = self.view_size();
+        let cursor = glam::Vec2::new(cursor_position.x() as f32, cursor_position.y() as f32);
+        let nearest = {
+            let this = self.rust();
+            let radius = if this.snap_radius.is_finite() {
+                this.snap_radius.abs()
+            } else {
+                0.
+            };
+            let camera = this.camera();
+            this.snap_points
+                .iter()
+                .filter_map(|&(position, source)| {
+                    let distance = camera
+                        .world_to_screen(
+                            camera.to_scene(position),
+                            size.width() as f32,
+                            size.height() as f32,
+                        )?
+                        .distance(cursor);
+                    (distance <= radius).then_some((distance, position, source))
+                })
+                .min_by(|(a, ..), (b, ..)| a.total_cmp(b))
+        };
+
+        let Some((_, position, source)) = nearest else {
+            self.set_snapped(None);
+            return transforms;
+        };
+
+        let this = self.rust();
+        let transforms = snapping::snap_to_point(
+            position,
+            this.frame(&this.drag_start_transforms),
+            this.active_mode.axes(),
+            &transforms,
+        );
+        let position = this.camera().to_scene(position);
+        self.set_snapped(Some((source, position)));
+        transforms
+    }
+
+    /// Set the snap target and its scene position the current translation snapped to
+    fn set_snapped(mut self: Pin<&mut Self>, snapped: Option<(usize, glam::Vec3)>) {
+        let index = snapped.map(|(index, _)| index);
+        let position = snapped.map_or(QVector3D::default(), |(_, position)| {
+            QVector3D::new(position.x, position.y, position.z)
+        });
+
+        if self.rust().snapped_index != index {
+            let snapped_to = match index {
+                Some(index) => ffi::qvariant_list_at(self.snap_targets(), index),
+                None => QVariant::default(),
+            };
+            {
+                let mut this = self.as_mut().rust_mut();
+                this.snapped_index = index;
+                this.snapped_to = snapped_to;
+            }
+            self.as_mut().snapped_to_changed();
+        }
+        if self.rust().snapped_position != position {
+            self.as_mut().rust_mut().snapped_position = position;
+            self.snapped_position_changed();
+        }
+    }
+
     fn update_interaction_impl(
         self: Pin<&mut Self>,
         interaction: transform_gizmo::GizmoInteraction,
@@ -564,41 +1664,462 @@ fn update_interaction_impl(
         transform_gizmo::GizmoResult,
         Vec<transform_gizmo::math::Transform>,
     )> {
-        let target_count = ffi::extract_target_count_from_qvariant(self.targets());
-        let mut positions = vec![QVector3D::default(); target_count];
-        let mut rotations = vec![QVector4D::default(); target_count];
-        let mut scales = vec![QVector3D::default(); target_count];
+        let proxy = self.rust().proxy(self.target_transforms());
 
-        ffi::extract_targets_from_qvariant(
-            self.targets(),
-            &mut positions,
-            &mut rotations,
-            &mut scales,
+        self.with_gizmo(|mut qobject, gizmo| {
+            qobject.as_mut().rust_mut().gizmo_updated_since_last_draw = true;
+            let Some(proxy) = proxy else {
+                return gizmo.update(interaction, qobject.target_transforms());
+            };
+
+            // Place the gizmo on the pivot, and apply its result to the targets ourselves
+            let (result, _) = gizmo.update(interaction, &[proxy])?;
+            let this = qobject.rust();
+            let start = &this.drag_start_transforms;
+            let transforms = pivot::apply(&result, this.frame(start), this.pivot(start), start);
+            Some((result, transforms))
+        })
+    }
+
+    /// Targets in world space
+    fn target_transforms(&self) -> &[transform_gizmo::math::Transform] {
+        self.rust().parsed_targets.world()
+    }
+
+    /// Targets as given, in the space of their `parentTransform`
+    fn local_target_transforms(&self) -> &[transform_gizmo::math::Transform] {
+        self.rust().parsed_targets.local()
+    }
+
+    /// Parse `targets` again, after it changed
+    fn parse_targets(mut self: Pin<&mut Self>) {
+        let targets = targets::Targets::parse(self.targets(), self.rust().rotation_format);
+        self.as_mut().rust_mut().parsed_targets = targets;
+    }
+
+    fn parse_snap_targets(mut self: Pin<&mut Self>) {
+        let point_count = ffi::extract_snap_point_count_from_qvariant(self.snap_targets());
+        let mut points = vec![[0.; 3]; point_count];
+        let mut sources = vec![0; point_count];
+        ffi::extract_snap_points_from_qvariant(
+            self.snap_targets(),
+            &self.rust().camera().origin.to_array(),
+            &mut points,
+            &mut sources,
         );
 
-        assert!(positions.len() == rotations.len());
-        assert!(positions.len() == scales.len());
-        let transforms = itertools::multizip((positions, rotations, scales))
-            .map(|(position, rotation, scale)| {
-                transform_gizmo::math::Transform::from_scale_rotation_translation(
-                    glam::Vec3::new(scale.x(), scale.y(), scale.z()).as_dvec3(),
-                    glam::Quat::from_xyzw(rotation.x(), rotation.y(), rotation.z(), rotation.w())
-                        .as_dquat(),
-                    glam::Vec3::new(position.x(), position.y(), position.z()).as_dvec3(),
-                )
+        self.as_mut().rust_mut().snap_points = points
+            .into_iter()
+            .map(glam::DVec3::from_array)
+            .zip(sources)
+            .collect();
+        // The snapped index refers to the previous list, the next drag move snaps again
+        self.set_snapped(None);
+    }
+
+    /// Mode of the handle under `cursor_pos`, `None` when nothing is hovered
+    ///
+    /// `transform_gizmo::Gizmo` doesn't expose the subgizmo it picks. Each enabled mode is picked
+    /// by its single-mode gizmo in `mode_gizmos`, and between overlapping handles the one whose
+    /// plane the cursor ray meets first wins.
+    fn focused_mode(
+        mut self: Pin<&mut Self>,
+        cursor_pos: (f32, f32),
+    ) -> Option<transform_gizmo::GizmoMode> {
+        let config = self.gizmo_config();
+        let hovered = self
+            .as_mut()
+            .with_gizmo(|_, gizmo| gizmo.pick_preview(cursor_pos));
+        if !hovered {
+            return None;
+        }
+        if let Some(mode_override) = config.mode_override {
+            return Some(mode_override);
+        }
+
+        let size = self.view_size();
+        let ratio = self.pixel_ratio();
+        let (width, height) = (size.width() as f32 * ratio, size.height() as f32 * ratio);
+        let mut gizmos = std::mem::take(&mut self.as_mut().rust_mut().mode_gizmos);
+
+        let this = self.rust();
+        let targets = this.parsed_targets.world();
+        let placement = this.placement(targets);
+        let center = this.center(targets);
+        let frame = glam::DQuat::from(placement.rotation);
+        let camera = this.camera();
+        let view_direction = (camera.rotation * glam::Vec3::NEG_Z).as_dvec3();
+        let (origin, direction) = camera.screen_ray(glam::Vec2::from(cursor_pos), width, height);
+
+        let hits = config
+            .modes
+            .iter()
+            .filter(|&mode| {
+                let gizmo = mode_gizmo(&mut gizmos, mode, config);
+                let _ = gizmo.update(transform_gizmo::GizmoInteraction::default(), &[placement]);
+                gizmo.pick_preview(cursor_pos)
             })
-            .collect::<Vec<_>>();
+            .map(|mode| {
+                let plane = handles::HandlePlane::of_handle(
+                    GizmoModeOverride::from(mode),
+                    center,
+                    frame,
+                    view_direction,
+                );
+                (mode, plane)
+            });
+        let focused = handles::closest_hit(hits, camera.to_world(origin), direction.as_dvec3());
 
-        self.with_gizmo(|mut qobject, gizmo| {
-            qobject.as_mut().rust_mut().gizmo_updated_since_last_draw = true;
-            gizmo.update(interaction, &transforms)
-        })
+        self.as_mut().rust_mut().mode_gizmos = gizmos;
+        focused
+    }
+
+    fn set_active_mode(mut self: Pin<&mut Self>, value: GizmoModeOverride) {
+        if self.rust().active_mode != value {
+            self.as_mut().rust_mut().active_mode = value;
+            self.active_mode_changed();
+        }
+    }
+
+    fn set_hovered_mode(mut self: Pin<&mut Self>, value: GizmoModeOverride) {
+        if self.rust().hovered_mode != value {
+            self.as_mut().rust_mut().hovered_mode = value;
+            self.hovered_mode_changed();
+        }
+    }
+
+    fn update_cursor(self: Pin<&mut Self>) {
+        let this = self.rust();
+        let shape = if !this.cursor_feedback {
+            None
+        } else if this.active_mode != GizmoModeOverride::NoOverride {
+            Some(this.drag_cursor)
+        } else if this.hovered_mode.is_translate() {
+            Some(this.translate_cursor)
+        } else if this.hovered_mode.is_rotate() {
+            Some(this.rotate_cursor)
+        } else if this.hovered_mode.is_scale() {
+            Some(this.scale_cursor)
+        } else {
+            None
+        };
+
+        match shape {
+            Some(shape) => self.set_cursor_shape(shape),
+            None => self.reset_cursor_shape(),
+        }
+    }
+
+    fn reset_readouts(mut self: Pin<&mut Self>) {
+        {
+            let mut this = self.as_mut().rust_mut();
+            this.translation_delta = QVector3D::default();
+            this.total_translation = QVector3D::default();
+            this.rotation_axis = QVector3D::default();
+            this.rotation_delta = 0.;
+            this.total_rotation_angle = 0.;
+            this.scale_delta = QVector3D::new(1., 1., 1.);
+            this.total_scale = QVector3D::new(1., 1., 1.);
+        }
+        self.as_mut().translation_delta_changed();
+        self.as_mut().total_translation_changed();
+        self.as_mut().rotation_axis_changed();
+        self.as_mut().rotation_delta_changed();
+        self.as_mut().total_rotation_angle_changed();
+        self.as_mut().scale_delta_changed();
+        self.as_mut().total_scale_changed();
+    }
+
+    /// Expose the last `transform_gizmo::GizmoResult` so QML can display it
+    fn update_readouts(mut self: Pin<&mut Self>, result: &transform_gizmo::GizmoResult) {
+        let to_qvector3d = |v: glam::DVec3| QVector3D::new(v.x as f32, v.y as f32, v.z as f32);
+
+        match *result {
+            transform_gizmo::GizmoResult::Translation { delta, total } => {
+                {
+                    let mut this = self.as_mut().rust_mut();
+                    this.translation_delta = to_qvector3d(delta.into());
+                    this.total_translation = to_qvector3d(total.into());
+                }
+                self.as_mut().translation_delta_changed();
+                self.as_mut().total_translation_changed();
+            }
+            transform_gizmo::GizmoResult::Rotation {
+                axis, delta, total, ..
+            } => {
+                {
+                    let mut this = self.as_mut().rust_mut();
+                    this.rotation_axis = to_qvector3d(axis.into());
+                    this.rotation_delta = delta as f32;
+                    this.total_rotation_angle = total as f32;
+                }
+                self.as_mut().rotation_axis_changed();
+                self.as_mut().rotation_delta_changed();
+                self.as_mut().total_rotation_angle_changed();
+            }
+            transform_gizmo::GizmoResult::Arcball { delta, total } => {
+                let (_, delta) = glam::DQuat::from(delta).to_axis_angle();
+                let (axis, total) = glam::DQuat::from(total).to_axis_angle();
+                {
+                    let mut this = self.as_mut().rust_mut();
+                    this.rotation_axis = to_qvector3d(axis);
+                    this.rotation_delta = delta as f32;
+                    this.total_rotation_angle = total as f32;
+                }
+                self.as_mut().rotation_axis_changed();
+                self.as_mut().rotation_delta_changed();
+                self.as_mut().total_rotation_angle_changed();
+            }
+            transform_gizmo::GizmoResult::Scale { total } => {
+                let total = glam::DVec3::from(total);
+                let previous = self.rust().total_scale;
+                let previous = glam::DVec3::new(
+                    previous.x() as f64,
+                    previous.y() as f64,
+                    previous.z() as f64,
+                );
+                let delta = total / previous;
+                let delta = glam::DVec3::select(delta.is_finite_mask(), delta, glam::DVec3::ONE);
+                {
+                    let mut this = self.as_mut().rust_mut();
+                    this.scale_delta = to_qvector3d(delta);
+                    this.total_scale = to_qvector3d(total);
+                }
+                self.as_mut().scale_delta_changed();
+                self.as_mut().total_scale_changed();
+            }
+        }
     }
 
     fn pick_preview(self: Pin<&mut Self>, cursor_position: QPointF) -> bool {
-        self.with_gizmo(|_, gizmo| {
-            gizmo.pick_preview((cursor_position.x() as f32, cursor_position.y() as f32))
-        })
+        let cursor_pos = self.as_ref().to_gizmo_position(&cursor_position);
+        self.with_gizmo(|_, gizmo| gizmo.pick_preview(cursor_pos))
+    }
+
+    fn handle_key_press(
+        mut self: Pin<&mut Self>,
+        key: i32,
+        modifiers: i32,
+        auto_repeat: bool,
+    ) -> bool {
+        let this = self.rust();
+        if this.keyboard_nudge_enabled && this.active_mode == GizmoModeOverride::NoOverride {
+            if let Some((axis, direction)) = nudge_axis(key) {
+                return self.nudge(axis, direction, modifiers);
+            }
+        }
+
+        if auto_repeat {
+            return false;
+        }
+
+        let typed = String::from(&this.numeric_input);
+        if this.numeric_input_enabled && this.active_mode != GizmoModeOverride::NoOverride {
+            if let Some(c) = numeric_input::key_to_char(key) {
+                if numeric_input::accepts(&typed, c) {
+                    self.edit_numeric_input(|text| text.push(c));
+                }
+                return true;
+            } else if key == QT_KEY_BACKSPACE && !typed.is_empty() {
+                self.edit_numeric_input(|text| {
+                    text.pop();
+                });
+                return true;
+            } else if key == this.cancel_key && !typed.is_empty() {
+                self.edit_numeric_input(String::clear);
+                return true;
+            }
+        }
+
+        if !this.modal_transform_enabled {
+            return false;
+        }
+
+        let modal_mode = this.modal_mode;
+        let plane = modifiers & QT_SHIFT_MODIFIER != 0;
+        let axis = [
+            this.constrain_x_key,
+            this.constrain_y_key,
+            this.constrain_z_key,
+        ]
+        .iter()
+        .position(|axis_key| *axis_key == key);
+
+        if key == this.translate_key {
+            self.begin_modal_transform(GizmoModeOverride::TranslateView)
+        } else if key == this.rotate_key {
+            self.begin_modal_transform(GizmoModeOverride::RotateView)
+        } else if key == this.scale_key {
+            self.begin_modal_transform(GizmoModeOverride::ScaleUniform)
+        } else if modal_mode == GizmoModeOverride::NoOverride {
+            false
+        } else if key == this.confirm_key
+            || (this.confirm_key == QT_KEY_RETURN && key == QT_KEY_ENTER)
+        {
+            self.confirm_modal_interaction();
+            true
+        } else if key == this.cancel_key {
+            self.cancel_modal_transform();
+            true
+        } else if let Some(axis) = axis {
+            self.begin_modal_transform(modal_mode.constrained(axis, plane))
+        } else {
+            false
+        }
+    }
+
+    fn set_numeric_input(mut self: Pin<&mut Self>, value: String) {
+        let value = QString::from(value.as_str());
+        if self.rust().numeric_input != value {
+            self.as_mut().rust_mut().numeric_input = value;
+            self.numeric_input_changed();
+        }
+    }
+
+    /// Edit the typed value and apply it to the targets of the current drag
+    fn edit_numeric_input(mut self: Pin<&mut Self>, edit: impl FnOnce(&mut String)) {
+        let mut text = String::from(&self.rust().numeric_input);
+        if text.is_empty() {
+            let pointer = {
+                let this = self.rust();
+                let to_dvec3 = |v: &QVector3D| glam::Vec3::new(v.x(), v.y(), v.z()).as_dvec3();
+                numeric_input::PointerManipulation {
+                    translation: to_dvec3(&this.total_translation),
+                    rotation_axis: to_dvec3(&this.rotation_axis),
+                    rotation_angle: this.total_rotation_angle as f64,
+                    scale: to_dvec3(&this.total_scale),
+                }
+            };
+            self.as_mut().rust_mut().numeric_pointer_manipulation = pointer;
+        }
+        edit(&mut text);
+        let empty = text.is_empty();
+        self.as_mut().set_numeric_input(text);
+
+        if empty {
+            // Go back to the manipulation done with the pointer
+            let interaction = self.rust().gizmo_last_interaction.unwrap_or_default();
+            let ratio = self.pixel_ratio() as f64;
+            let cursor_position = QPointF::new(
+                interaction.cursor_pos.0 as f64 / ratio,
+                interaction.cursor_pos.1 as f64 / ratio,
+            );
+            self.update_interaction(cursor_position, interaction.hovered, false, true);
+        } else {
+            self.apply_numeric_input();
+        }
+    }
+
+    fn apply_numeric_input(mut self: Pin<&mut Self>) {
+        let transforms = {
+            let this = self.rust();
+            let targets = &this.drag_start_transforms;
+            match numeric_input::parse(&String::from(&this.numeric_input)) {
+                Some(value) => this
+                    .numeric_transform(this.active_mode, targets, this.numeric_pointer_manipulation)
+                    .apply(value),
+                // Incomplete value such as `-`
+                None => targets.clone(),
+            }
+        };
+
+        self.emit_transforms(&transforms);
+    }
+
+    /// Move, rotate or scale the targets by one snap increment along `axis`
+    fn nudge(mut self: Pin<&mut Self>, axis: usize, direction: f64, modifiers: i32) -> bool {
+        let config = self.gizmo_config();
+        let (mode, value) = if modifiers & QT_CONTROL_MODIFIER != 0 {
+            (
+                GizmoModeOverride::RotateView.constrained(axis, false),
+                (config.snap_angle as f64).to_degrees() * direction,
+            )
+        } else if modifiers & QT_SHIFT_MODIFIER != 0 {
+            (
+                GizmoModeOverride::ScaleUniform.constrained(axis, false),
+                (1. + config.snap_scale as f64).powf(direction),
+            )
+        } else {
+            (
+                GizmoModeOverride::TranslateView.constrained(axis, false),
+                config.snap_distance as f64 * direction,
+            )
+        };
+
+        let enabled = Option::<transform_gizmo::GizmoMode>::from(mode)
+            .is_some_and(|mode| config.modes.contains(mode));
+        let targets = self.target_transforms();
+        if !enabled || targets.is_empty() || !self.is_visible() {
+            return false;
+        }
+
+        let transforms = self
+            .rust()
+            .numeric_transform(mode, targets, Default::default())
+            .apply(numeric_input::NumericValue::Absolute(value));
+        self.as_mut().emit_transforms(&transforms);
+        self.update();
+        true
+    }
+
+    /// Start or restart a modal transform in `mode` from the current cursor position
+    fn begin_modal_transform(mut self: Pin<&mut Self>, mode: GizmoModeOverride) -> bool {
+        let modal = self.rust().modal_mode != GizmoModeOverride::NoOverride;
+        let dragging_handle = !modal && self.rust().active_mode != GizmoModeOverride::NoOverride;
+        let enabled = Option::<transform_gizmo::GizmoMode>::from(mode)
+            .is_some_and(|mode| self.gizmo_config().modes.contains(mode));
+        let has_targets = !self.rust().parsed_targets.is_empty();
+        if dragging_handle || !enabled || !has_targets || !self.is_visible() {
+            return false;
+        }
+
+        if modal {
+            self.as_mut().restore_modal_start_targets();
+        } else {
+            let targets = self.targets().clone();
+            self.as_mut().rust_mut().modal_start_targets = targets;
+            self.as_mut().set_modal_interaction(true);
+        }
+
+        {
+            let mut this = self.as_mut().rust_mut();
+            this.modal_mode = mode;
+            // Start over from the initial transforms with the new mode
+            this.gizmo = None;
+        }
+
+        let cursor_position = self.cursor_position();
+        self.update_interaction(cursor_position, true, true, true);
+        true
+    }
+
+    fn confirm_modal_interaction(mut self: Pin<&mut Self>) {
+        if self.rust().modal_mode == GizmoModeOverride::NoOverride {
+            return;
+        }
+
+        {
+            let mut this = self.as_mut().rust_mut();
+            this.modal_mode = GizmoModeOverride::NoOverride;
+            this.modal_start_targets = QVariant::default();
+            this.gizmo = None;
+        }
+        self.as_mut().set_modal_interaction(false);
+
+        let cursor_position = self.cursor_position();
+        self.update_interaction(cursor_position, false, false, false);
+    }
+
+    fn cancel_modal_transform(mut self: Pin<&mut Self>) {
+        self.as_mut().restore_modal_start_targets();
+        self.confirm_modal_interaction();
+    }
+
+    fn restore_modal_start_targets(mut self: Pin<&mut Self>) {
+        let targets = self.rust().modal_start_targets.clone();
+        self.as_mut().rust_mut().targets = targets.clone();
+        self.as_mut().parse_targets();
+        self.as_mut().transform_updated(targets);
     }
 
     fn with_gizmo<T>(
@@ -618,16 +2139,43 @@ fn with_gizmo<T>(
         result
     }
 
+    /// Ratio of the physical pixels `transform_gizmo` works with to the item logical pixels
+    fn pixel_ratio(&self) -> f32 {
+        let pixels_per_point = self.rust().pixels_per_point;
+        if pixels_per_point.is_finite() && pixels_per_point > 0. {
+            pixels_per_point
+        } else {
+            self.device_pixel_ratio() as f32
+        }
+    }
+
+    /// Cursor `position` in view logical pixels, in the physical pixels of `transform_gizmo`
+    fn to_gizmo_position(&self, position: &QPointF) -> (f32, f32) {
+        let ratio = self.pixel_ratio();
+        (position.x() as f32 * ratio, position.y() as f32 * ratio)
+    }
+
+    /// The shared style when set, which takes over the gizmo own visuals
+    fn visual_style(&self) -> style::Style {
+        // The style is watched by a QPointer, so it is alive or null
+        match unsafe { self.style().as_ref() } {
+            Some(style) => style::Style::from(gizmo_style_from_base(style)),
+            None => style::Style::from(self.rust()),
+        }
+    }
+
     fn gizmo_config(&self) -> transform_gizmo::GizmoConfig {
-        let size = self.size();
+        let size = self.view_size();
         let this = self.rust();
-        let view_matrix = this.view_matrix();
+        // Computed in double precision for targets far from the origin
+        let view_matrix = this.camera().view_dmatrix();
         let width = size.width() as f32;
         let height = size.height() as f32;
         let projection_matrix = this.projection_matrix(width, height);
         let orientation = this.orientation.into();
         let pivot_point = this.pivot_point.into();
-        let snapping = this.snapping;
+        // Absolute snapping is applied on the result of the gizmo
+        let snapping = this.snapping && this.snap_mode == GizmoSnapMode::Relative;
         let snap_angle = if this.snap_angle.is_finite() {
             this.snap_angle.abs()
         } else {
@@ -643,11 +2191,7 @@ fn gizmo_config(&self) -> transform_gizmo::GizmoConfig {
         } else {
             transform_gizmo::config::DEFAULT_SNAP_SCALE
         };
-        let pixels_per_point = if this.pixels_per_point.is_finite() {
-            this.pixels_per_point.abs()
-        } else {
-            1.0
-        };
+        let pixels_per_point = self.pixel_ratio();
         let modes = {
             let mut modes = transform_gizmo::EnumSet::<transform_gizmo::GizmoMode>::new();
             if this.translate_enabled {
@@ -684,62 +2228,81 @@ fn gizmo_config(&self) -> transform_gizmo::GizmoConfig {
             if this.scale_uniform_enabled {
                 modes.insert(transform_gizmo::GizmoMode::ScaleUniform);
             }
+
+            // Hide the translation handles along an axis locked for every target, whatever the
+            // orientation the gizmo axes match the locked ones in
+            let targets = this.parsed_targets.world();
+            let frame = this.frame(targets);
+            let locked_directions: Vec<Vec<_>> = this
+                .target_constraints()
+                .iter()
+                .zip(this.target_parents())
+                .map(|((world, local), parent)| {
+                    world
+                        .locked_directions(glam::DMat4::IDENTITY)
+                        .chain(local.locked_directions(*parent))
+                        .collect()
+                })
+                .collect();
+            let translations = [
+                GizmoModeOverride::TranslateX,
+                GizmoModeOverride::TranslateY,
+                GizmoModeOverride::TranslateZ,
+                GizmoModeOverride::TranslateXY,
+                GizmoModeOverride::TranslateXZ,
+                GizmoModeOverride::TranslateYZ,
+            ];
+            for mode in translations {
+                let axes = mode.axes().to_array();
+                let locked = (0..3).any(|axis| {
+                    axes[axis] != 0.
+                        && constraints::is_locked_direction(
+                            frame * glam::DVec3::AXES[axis],
+                            &locked_directions,
+                        )
+                });
+                if let Some(mode) =
+                    Option::<transform_gizmo::GizmoMode>::from(mode).filter(|_| locked)
+                {
+                    modes.remove(mode);
+                }
+            }
+            let allowed_rotations = [
+                (this.allow_rotation_x, transform_gizmo::GizmoMode::RotateX),
+                (this.allow_rotation_y, transform_gizmo::GizmoMode::RotateY),
+                (this.allow_rotation_z, transform_gizmo::GizmoMode::RotateZ),
+            ];
+            for (allowed, mode) in allowed_rotations {
+                if !allowed {
+                    // View rotations turn around any axis
+                    modes.remove(mode);
+                    modes.remove(transform_gizmo::GizmoMode::RotateView);
+                }
+            }
             modes
         };
-        let mode_override = this.mode_override.into();
+        let mode_override = if this.modal_mode != GizmoModeOverride::NoOverride {
+            this.modal_mode
+        } else {
+            this.mode_override
+        }
+        .into();
 
-        let visuals = transform_gizmo::GizmoVisuals {
-            x_color: transform_gizmo::Color32::from_rgb(
-                this.x_color.red() as u8,
-                this.x_color.green() as u8,
-                this.x_color.blue() as u8,
-            ),
-            y_color: transform_gizmo::Color32::from_rgb(
-                this.y_color.red() as u8,
-                this.y_color.green() as u8,
-                this.y_color.blue() as u8,
-            ),
-            z_color: transform_gizmo::Color32::from_rgb(
-                this.z_color.red() as u8,
-                this.z_color.green() as u8,
-                this.z_color.blue() as u8,
-            ),
-            s_color: transform_gizmo::Color32::from_rgb(
-                this.s_color.red() as u8,
-                this.s_color.green() as u8,
-                this.s_color.blue() as u8,
-            ),
-            inactive_alpha: if this.inactive_alpha.is_finite() {
-                this.inactive_alpha.abs()
-            } else {
-                0.7
-            },
-            highlight_alpha: if this.highlight_alpha.is_finite() {
-                this.highlight_alpha.abs()
-            } else {
-                1.0
-            },
-            stroke_width: if this.stroke_width.is_finite() {
-                this.stroke_width.abs()
-            } else {
-                4.0
-            },
-            gizmo_size: if this.gizmo_size.is_finite() {
-                this.gizmo_size.abs()
-            } else {
-                75.0
-            },
-            ..Default::default()
+        let focused = if this.active_mode != GizmoModeOverride::NoOverride {
+            this.active_mode
+        } else {
+            this.hovered_mode
         };
+        let visuals = self.visual_style().visuals(modes, focused);
 
         transform_gizmo::GizmoConfig {
-            view_matrix: view_matrix.as_dmat4().into(),
+            view_matrix: view_matrix.into(),
             projection_matrix: projection_matrix.as_dmat4().into(),
             viewport: transform_gizmo::Rect {
                 min: transform_gizmo::math::Pos2 { x: 0., y: 0. },
                 max: transform_gizmo::math::Pos2 {
-                    x: width,
-                    y: height,
+                    x: width * pixels_per_point,
+                    y: height * pixels_per_point,
                 },
             },
             modes,
@@ -755,6 +2318,98 @@ fn gizmo_config(&self) -> transform_gizmo::GizmoConfig {
         }
     }
 
+    /// Publish the handles placed in the world for `GizmoGeometry`
+    ///
+    /// Each handle is drawn by its own single-mode gizmo, so that its vertices are brought back on
+    /// the plane of this handle.
+    fn update_geometry_data(mut self: Pin<&mut Self>) {
+        let mut vertices = Vec::new();
+        let mut colors = Vec::new();
+        let mut indices = Vec::new();
+
+        if !self.rust().parsed_targets.is_empty() && self.is_visible() {
+            let config = self.gizmo_config();
+            let size = self.view_size();
+            let ratio = self.pixel_ratio();
+            let (width, height) = (size.width() as f32 * ratio, size.height() as f32 * ratio);
+            let mut gizmos = std::mem::take(&mut self.as_mut().rust_mut().mode_gizmos);
+
+            let this = self.rust();
+            let targets = self.target_transforms();
+            let focused = if this.active_mode != GizmoModeOverride::NoOverride {
+                this.active_mode
+            } else {
+                this.hovered_mode
+            };
+            // Only the dragged handle is shown during a drag
+            let modes = match Option::<transform_gizmo::GizmoMode>::from(this.active_mode)
+                .or(config.mode_override)
+            {
+                Some(mode) => transform_gizmo::EnumSet::only(mode),
+                None => config.modes,
+            };
+            let placement = this.placement(targets);
+            let center = this.center(targets);
+            let frame = glam::DQuat::from(placement.rotation);
+            let camera = this.camera();
+            let view_direction = (camera.rotation * glam::Vec3::NEG_Z).as_dvec3();
+            let facing = handles::HandlePlane::facing(center, view_direction);
+            // Vertices of planes seen edge-on would be lifted far away, they go on the plane
+            // facing the camera past twice the gizmo size
+            let max_distance = {
+                let depth = (center - camera.to_world(camera.position)).dot(view_direction);
+                let pixel_size = 2. * depth * (camera.vertical_fov as f64 / 2.).to_radians().tan()
+                    / height as f64;
+                2. * (config.visuals.gizmo_size * config.pixels_per_point) as f64 * pixel_size
+            };
+
+            for mode in modes {
+                let mode_override = GizmoModeOverride::from(mode);
+                let mut visuals = config.visuals;
+                // The handle isn't hovered by the single-mode gizmo, show it focused through the
+                // visuals
+                if mode_override == focused {
+                    visuals.inactive_alpha = visuals.highlight_alpha;
+                    if let Some(color) = visuals.highlight_color {
+                        visuals.x_color = color;
+                        visuals.y_color = color;
+                        visuals.z_color = color;
+                        visuals.s_color = color;
+                    }
+                }
+                let gizmo = mode_gizmo(
+                    &mut gizmos,
+                    mode,
+                    transform_gizmo::GizmoConfig { visuals, ..config },
+                );
+                let _ = gizmo.update(transform_gizmo::GizmoInteraction::default(), &[placement]);
+                let draw_data = gizmo.draw();
+
+                let plane =
+                    handles::HandlePlane::of_handle(mode_override, center, frame, view_direction);
+                let first = vertices.len() as u32;
+                vertices.extend(draw_data.vertices.iter().map(|&[x, y]| {
+                    let (origin, direction) =
+                        camera.screen_ray(glam::Vec2::new(x, y), width, height);
+                    let (origin, direction) = (camera.to_world(origin), direction.as_dvec3());
+                    let point = plane
+                        .intersect(origin, direction)
+                        .filter(|point| point.distance(center) <= max_distance)
+                        .or_else(|| facing.intersect(origin, direction))
+                        .unwrap_or(center);
+                    // The geometry is drawn in the QtQuick3D scene
+                    camera.to_scene(point).to_array()
+                }));
+                colors.extend_from_slice(&draw_data.colors);
+                indices.extend(draw_data.indices.iter().map(|index| first + index));
+            }
+
+            self.as_mut().rust_mut().mode_gizmos = gizmos;
+        }
+
+        self.set_geometry_data(&vertices, &colors, &indices);
+    }
+
     unsafe fn update_paint_node(
         mut self: Pin<&mut Self>,
         old_node: *mut QSGNode,
@@ -773,20 +2428,69 @@ unsafe fn update_paint_node(
         }
         self.as_mut().rust_mut().gizmo_updated_since_last_draw = false;
 
-        self.with_gizmo(|qobject, gizmo| unsafe {
-            let target_count = ffi::extract_target_count_from_qvariant(qobject.targets());
-            let draw_data = if target_count > 0 && qobject.is_visible() {
+        self.with_gizmo(|mut qobject, gizmo| unsafe {
+            let targets = qobject.target_transforms();
+            let draw_data = if !targets.is_empty() && qobject.is_visible() {
                 gizmo.draw()
             } else {
                 transform_gizmo::GizmoDrawData::default()
             };
 
-            ffi::gizmo_update_paint_node(
+            let size = qobject.view_size();
+            let ratio = qobject.pixel_ratio();
+
+            if !qobject.rust().overlay_enabled {
+                qobject.as_mut().rust_mut().overlay_frame = OverlayFrame::default();
+                return ffi::gizmo_update_paint_node(old_node, &[], &[], &[]);
+            }
+
+            // The feedback is drawn below the handles
+            let mut mesh = OverlayMesh::default();
+            let labels = if qobject.rust().show_labels && !targets.is_empty() {
+                let view_size = glam::Vec2::new(size.width() as f32, size.height() as f32);
+                let config = qobject.gizmo_config();
+                let this = qobject.rust();
+                let color = qobject.visual_style().handle_color(this.active_mode);
+                this.drag_feedback(
+                    targets,
+                    view_size,
+                    config.visuals.gizmo_size / 2.,
+                    color,
+                    &mut mesh,
+                )
+            } else {
+                Vec::new()
+            };
+
+            let mut view_to_item = [0.; 6];
+            qobject.view_to_item_transform(&mut view_to_item);
+            let frame = OverlayFrame::new(mesh, labels, &draw_data, ratio, view_to_item);
+            // The node already shows this frame, typically when the camera didn't move
+            if !old_node.is_null() && qobject.rust().overlay_frame == frame {
+                return old_node;
+            }
+
+            let positions: Vec<QPointF> = frame
+                .labels
+                .iter()
+                .map(|label| QPointF::new(label.position.x as f64, label.position.y as f64))
+                .collect();
+            let texts: Vec<QString> = frame
+                .labels
+                .iter()
+                .map(|label| QString::from(&label.text))
+                .collect();
+
+            let mesh = &frame.mesh;
+            let node = ffi::gizmo_update_paint_node(
                 old_node,
-                &draw_data.vertices,
-                &draw_data.colors,
-                &draw_data.indices,
-            )
+                &mesh.vertices,
+                &mesh.rgba8_colors(),
+                &mesh.indices,
+            );
+            qobject.as_mut().update_labels(node, &positions, &texts);
+            qobject.as_mut().rust_mut().overlay_frame = frame;
+            node
         })
     }
 }
@@ -0,0 +1,342 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Constraints applied to the transforms produced by the gizmo.
+//!
+//! The gizmo constraints are in world space. The constraints of a target are in the space of its
+//! `parentTransform`, like its `position`, and are applied once the target is brought back there.
+//! Rotations are constrained around the axes of the gizmo frame either way.
+
+use glam::{BVec3, DMat4, DQuat, DVec3};
+use transform_gizmo::math::Transform;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Constraints {
+    /// Axes along which translations are locked
+    pub locked_translation: BVec3,
+    /// Axes of the gizmo frame around which rotations are allowed
+    pub allowed_rotation: BVec3,
+    pub min_position: DVec3,
+    pub max_position: DVec3,
+    pub min_scale: DVec3,
+    pub max_scale: DVec3,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Self {
+            locked_translation: BVec3::FALSE,
+            allowed_rotation: BVec3::TRUE,
+            min_position: DVec3::NEG_INFINITY,
+            max_position: DVec3::INFINITY,
+            min_scale: DVec3::NEG_INFINITY,
+            max_scale: DVec3::INFINITY,
+        }
+    }
+}
+
+impl Constraints {
+    /// Constraints satisfying both `self` and `other`
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            locked_translation: self.locked_translation | other.locked_translation,
+            allowed_rotation: self.allowed_rotation & other.allowed_rotation,
+            min_position: self.min_position.max(other.min_position),
+            max_position: self.max_position.min(other.max_position),
+            min_scale: self.min_scale.max(other.min_scale),
+            max_scale: self.max_scale.min(other.max_scale),
+        }
+    }
+
+    /// Unit world directions of the locked axes, for constraints in the space of `basis`
+    pub fn locked_directions(&self, basis: DMat4) -> impl Iterator<Item = DVec3> + '_ {
+        (0..3)
+            .filter(|&axis| self.locked_translation.test(axis))
+            .filter_map(move |axis| basis.col(axis).truncate().try_normalize())
+    }
+
+    /// Constrain `transform`, `start` being the target before it was manipulated along the gizmo
+    /// axes oriented by `frame`
+    ///
+    /// Only the orientation is constrained by the allowed rotations, the position a rotation
+    /// around a common pivot gave the target is kept.
+    pub fn apply(&self, transform: &Transform, start: &Transform, frame: DQuat) -> Transform {
+        let translation = DVec3::select(
+            self.locked_translation,
+            start.translation.into(),
+            transform.translation.into(),
+        );
+        // Not `clamp`, which panics when bounds are crossed
+        let translation = translation.max(self.min_position).min(self.max_position);
+        let scale = DVec3::from(transform.scale)
+            .max(self.min_scale)
+            .min(self.max_scale);
+        let rotation =
+            self.constrain_rotation(transform.rotation.into(), start.rotation.into(), frame);
+
+        Transform {
+            translation: translation.into(),
+            rotation: rotation.into(),
+            scale: scale.into(),
+        }
+    }
+
+    /// Project the rotation from `start` to `rotation` onto the allowed axes of `frame`
+    fn constrain_rotation(&self, rotation: DQuat, start: DQuat, frame: DQuat) -> DQuat {
+        if self.allowed_rotation.all() {
+            return rotation;
+        }
+
+        // Rotation of the drag, in the gizmo frame
+        let delta = frame.inverse() * rotation * start.inverse() * frame;
+        let allowed = DVec3::select(self.allowed_rotation, DVec3::ONE, DVec3::ZERO);
+        let delta = match self.allowed_rotation.bitmask().count_ones() {
+            0 => DQuat::IDENTITY,
+            1 => twist(delta, allowed),
+            // Keep the swing, which turns around an axis orthogonal to the locked one
+            _ => delta * twist(delta, DVec3::ONE - allowed).inverse(),
+        };
+        (frame * delta * frame.inverse() * start).normalize()
+    }
+}
+
+/// Whether a translation along the unit world `direction` is locked for every target, each target
+/// being locked along its unit world `locked_directions`
+pub fn is_locked_direction(direction: DVec3, locked_directions: &[Vec<DVec3>]) -> bool {
+    !locked_directions.is_empty()
+        && locked_directions.iter().all(|locked| {
+            locked
+                .iter()
+                .any(|locked| locked.dot(direction).abs() > 1. - 1e-9)
+        })
+}
+
+/// Part of `rotation` turning around the unit `axis`, from its swing-twist decomposition
+fn twist(rotation: DQuat, axis: DVec3) -> DQuat {
+    let projection = axis * rotation.xyz().dot(axis);
+    let twist = DQuat::from_xyzw(projection.x, projection.y, projection.z, rotation.w);
+    // A half turn swing doesn't turn around `axis` at all
+    if twist.length_squared() < f64::EPSILON {
+        DQuat::IDENTITY
+    } else {
+        twist.normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gizmo::hierarchy;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn transform(rotation: DQuat, translation: DVec3) -> Transform {
+        Transform::from_scale_rotation_translation(DVec3::ONE, rotation, translation)
+    }
+
+    fn assert_rotation_eq(a: impl Into<DQuat>, b: DQuat) {
+        let a = a.into();
+        // `q` and `-q` are the same rotation
+        assert!(a.dot(b).abs() > 1. - EPSILON, "{a} != {b}");
+    }
+
+    fn allowing(allowed_rotation: BVec3) -> Constraints {
+        Constraints {
+            allowed_rotation,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn locked_translation_keeps_start_coordinate() {
+        let constraints = Constraints {
+            locked_translation: BVec3::new(false, true, false),
+            ..Default::default()
+        };
+        let start = transform(DQuat::IDENTITY, DVec3::new(1., 2., 3.));
+        let moved = transform(DQuat::IDENTITY, DVec3::new(4., 5., 6.));
+        let constrained = constraints.apply(&moved, &start, DQuat::IDENTITY);
+
+        let translation = DVec3::from(constrained.translation);
+        assert_eq!(translation, DVec3::new(4., 2., 6.));
+    }
+
+    #[test]
+    fn bounds_clamp_position_and_scale() {
+        let constraints = Constraints {
+            min_position: DVec3::splat(-1.),
+            max_position: DVec3::splat(1.),
+            min_scale: DVec3::splat(0.5),
+            max_scale: DVec3::splat(2.),
+            ..Default::default()
+        };
+        let start = transform(DQuat::IDENTITY, DVec3::ZERO);
+        let moved = Transform::from_scale_rotation_translation(
+            DVec3::new(0.1, 1., 3.),
+            DQuat::IDENTITY,
+            DVec3::new(-5., 0.5, 5.),
+        );
+        let constrained = constraints.apply(&moved, &start, DQuat::IDENTITY);
+
+        assert_eq!(
+            DVec3::from(constrained.translation),
+            DVec3::new(-1., 0.5, 1.)
+        );
+        assert_eq!(DVec3::from(constrained.scale), DVec3::new(0.5, 1., 2.));
+    }
+
+    #[test]
+    fn crossed_bounds_do_not_panic() {
+        let constraints = Constraints {
+            min_position: DVec3::splat(1.),
+            max_position: DVec3::splat(-1.),
+            ..Default::default()
+        };
+        let start = transform(DQuat::IDENTITY, DVec3::ZERO);
+        let constrained = constraints.apply(&start, &start, DQuat::IDENTITY);
+
+        assert!(DVec3::from(constrained.translation).is_finite());
+    }
+
+    #[test]
+    fn intersect_keeps_strictest_constraints() {
+        let a = Constraints {
+            locked_translation: BVec3::new(true, false, false),
+            allowed_rotation: BVec3::new(true, true, false),
+            min_position: DVec3::splat(-2.),
+            max_scale: DVec3::splat(3.),
+            ..Default::default()
+        };
+        let b = Constraints {
+            locked_translation: BVec3::new(false, true, false),
+            allowed_rotation: BVec3::new(false, true, true),
+            min_position: DVec3::splat(-1.),
+            max_scale: DVec3::splat(4.),
+            ..Default::default()
+        };
+        let both = a.intersect(&b);
+
+        assert_eq!(both.locked_translation, BVec3::new(true, true, false));
+        assert_eq!(both.allowed_rotation, BVec3::new(false, true, false));
+        assert_eq!(both.min_position, DVec3::splat(-1.));
+        assert_eq!(both.max_scale, DVec3::splat(3.));
+    }
+
+    #[test]
+    fn rotation_around_allowed_axis_is_kept() {
+        let start = transform(DQuat::from_rotation_x(0.3), DVec3::ZERO);
+        let rotation = DQuat::from_rotation_y(0.5) * DQuat::from_rotation_x(0.3);
+        let moved = transform(rotation, DVec3::ZERO);
+        let constrained =
+            allowing(BVec3::new(false, true, false)).apply(&moved, &start, DQuat::IDENTITY);
+
+        assert_rotation_eq(constrained.rotation, rotation);
+    }
+
+    #[test]
+    fn rotation_around_forbidden_axis_is_removed() {
+        let start = transform(DQuat::from_rotation_y(0.3), DVec3::ZERO);
+        let moved = transform(
+            DQuat::from_rotation_x(0.5) * DQuat::from_rotation_y(0.3),
+            DVec3::ZERO,
+        );
+        let constrained =
+            allowing(BVec3::new(false, true, false)).apply(&moved, &start, DQuat::IDENTITY);
+
+        assert_rotation_eq(constrained.rotation, DQuat::from_rotation_y(0.3));
+    }
+
+    #[test]
+    fn allowed_rotation_follows_frame() {
+        // The frame X axis is the world Y axis
+        let frame = DQuat::from_rotation_z(90_f64.to_radians());
+        let start = transform(DQuat::IDENTITY, DVec3::ZERO);
+        let allowed = allowing(BVec3::new(true, false, false));
+
+        let around_y = transform(DQuat::from_rotation_y(0.5), DVec3::ZERO);
+        let constrained = allowed.apply(&around_y, &start, frame);
+        assert_rotation_eq(constrained.rotation, DQuat::from_rotation_y(0.5));
+
+        let around_x = transform(DQuat::from_rotation_x(0.5), DVec3::ZERO);
+        let constrained = allowed.apply(&around_x, &start, frame);
+        assert_rotation_eq(constrained.rotation, DQuat::IDENTITY);
+    }
+
+    #[test]
+    fn locked_axis_twist_is_removed() {
+        let start = transform(DQuat::IDENTITY, DVec3::ZERO);
+        let swing = DQuat::from_rotation_x(0.4);
+        let moved = transform(swing * DQuat::from_rotation_z(0.7), DVec3::ZERO);
+        let constrained =
+            allowing(BVec3::new(true, true, false)).apply(&moved, &start, DQuat::IDENTITY);
+
+        assert_rotation_eq(constrained.rotation, swing);
+    }
+
+    #[test]
+    fn no_allowed_rotation_keeps_start_rotation() {
+        let start = transform(DQuat::from_rotation_z(0.2), DVec3::ZERO);
+        let moved = transform(DQuat::from_rotation_x(1.), DVec3::X);
+        let constrained = allowing(BVec3::FALSE).apply(&moved, &start, DQuat::IDENTITY);
+
+        assert_rotation_eq(constrained.rotation, DQuat::from_rotation_z(0.2));
+        assert_eq!(DVec3::from(constrained.translation), DVec3::X);
+    }
+
+    #[test]
+    fn locked_directions_follow_the_constraints_space() {
+        let constraints = Constraints {
+            locked_translation: BVec3::new(true, false, true),
+            ..Default::default()
+        };
+        let world: Vec<_> = constraints.locked_directions(DMat4::IDENTITY).collect();
+        assert_eq!(world, [DVec3::X, DVec3::Z]);
+
+        // Parent turned a quarter around Z and stretched, its X axis is the world Y axis
+        let parent = DMat4::from_scale_rotation_translation(
+            DVec3::new(3., 1., 1.),
+            DQuat::from_rotation_z(90_f64.to_radians()),
+            DVec3::new(5., 6., 7.),
+        );
+        let parent_space: Vec<_> = constraints.locked_directions(parent).collect();
+        assert!(parent_space[0].abs_diff_eq(DVec3::Y, EPSILON));
+        assert!(parent_space[1].abs_diff_eq(DVec3::Z, EPSILON));
+    }
+
+    #[test]
+    fn direction_is_locked_only_when_locked_for_every_target() {
+        let locked = vec![vec![DVec3::X], vec![DVec3::NEG_X, DVec3::Y]];
+        assert!(is_locked_direction(DVec3::X, &locked));
+        assert!(!is_locked_direction(DVec3::Y, &locked));
+        // Diagonal handles still move along the unlocked axis
+        assert!(!is_locked_direction(
+            DVec3::new(1., 1., 0.).normalize(),
+            &locked
+        ));
+        assert!(!is_locked_direction(DVec3::X, &[]));
+    }
+
+    #[test]
+    fn parent_space_lock_keeps_the_local_coordinate() {
+        let parent = DMat4::from_rotation_z(90_f64.to_radians());
+        let start_local = transform(DQuat::IDENTITY, DVec3::new(1., 2., 3.));
+        let start = hierarchy::to_world(parent, &start_local);
+        // Moved along world X, which is the parent -Y axis
+        let moved = transform(
+            DQuat::IDENTITY,
+            DVec3::from(start.translation) + DVec3::new(4., 0., 0.),
+        );
+        let local = hierarchy::to_local(parent, &moved, &start, &start_local);
+        let constraints = Constraints {
+            locked_translation: BVec3::new(false, true, false),
+            ..Default::default()
+        };
+
+        let constrained = constraints.apply(&local, &start_local, DQuat::IDENTITY);
+        assert!(
+            DVec3::from(constrained.translation).abs_diff_eq(DVec3::new(1., 2., 3.), EPSILON),
+            "{:?}",
+            constrained.translation
+        );
+    }
+}
@@ -0,0 +1,222 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Handles of the gizmo placed in the world, for `GizmoGeometry` to depth test them against the
+//! scene.
+//!
+//! `transform_gizmo` only draws the handles in screen space. Each vertex is brought back in the
+//! world by intersecting its view ray with the plane its handle lies on: the plane of a rotation
+//! ring or of a plane handle, the plane through an axis facing the camera for axis handles, and
+//! the plane facing the camera through the gizmo center for view handles.
+
+use glam::{DQuat, DVec3};
+
+use super::ffi::GizmoModeOverride;
+
+/// Plane a handle lies on, as a point and a unit normal
+#[derive(Debug, Clone, Copy)]
+pub struct HandlePlane {
+    pub origin: DVec3,
+    pub normal: DVec3,
+}
+
+impl HandlePlane {
+    /// Plane facing the camera looking along `view_direction`, through `origin`
+    pub fn facing(origin: DVec3, view_direction: DVec3) -> Self {
+        Self {
+            origin,
+            normal: view_direction.normalize_or(DVec3::NEG_Z),
+        }
+    }
+
+    /// Plane of the handle of `mode`, of a gizmo at `center` oriented by `frame` and seen along
+    /// `view_direction`
+    pub fn of_handle(
+        mode: GizmoModeOverride,
+        center: DVec3,
+        frame: DQuat,
+        view_direction: DVec3,
+    ) -> Self {
+        let facing = Self::facing(center, view_direction);
+        let axes = mode.axes();
+        let normal = match axes.element_sum() as u32 {
+            // Rotation rings lie in the plane orthogonal to their axis
+            1 if mode.is_rotate() => Some(frame * axes),
+            // Axis handles lie on their axis, the plane through it facing the camera keeps their
+            // width. An axis seen end-on has no such plane.
+            1 => {
+                let axis = frame * axes;
+                axis.cross(facing.normal).cross(axis).try_normalize()
+            }
+            2 => Some(frame * (DVec3::ONE - axes)),
+            _ => None,
+        };
+
+        match normal {
+            Some(normal) => Self {
+                origin: center,
+                normal,
+            },
+            None => facing,
+        }
+    }
+
+    /// Point of the plane on the ray from `origin` along `direction`, `None` when the ray is
+    /// parallel to the plane or points away from it
+    pub fn intersect(&self, origin: DVec3, direction: DVec3) -> Option<DVec3> {
+        let denominator = direction.dot(self.normal);
+        if denominator.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let distance = (self.origin - origin).dot(self.normal) / denominator;
+        (distance > 0.).then(|| origin + direction * distance)
+    }
+}
+
+/// Key of the handle among `hits` whose plane the ray from `origin` along `direction` meets first,
+/// like `transform_gizmo` picking the closest subgizmo. Handles whose plane the ray misses come
+/// last, in the order of `hits`.
+pub fn closest_hit<T>(
+    hits: impl IntoIterator<Item = (T, HandlePlane)>,
+    origin: DVec3,
+    direction: DVec3,
+) -> Option<T> {
+    hits.into_iter()
+        .map(|(key, plane)| {
+            let distance = plane
+                .intersect(origin, direction)
+                .map_or(f64::INFINITY, |point| point.distance(origin));
+            (key, distance)
+        })
+        .reduce(|closest, hit| if hit.1 < closest.1 { hit } else { closest })
+        .map(|(key, _)| key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_vec_eq(a: DVec3, b: DVec3) {
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    fn assert_parallel(a: DVec3, b: DVec3) {
+        assert!(a.cross(b).length() < EPSILON, "{a} is not parallel to {b}");
+    }
+
+    #[test]
+    fn rotation_ring_plane_is_orthogonal_to_axis() {
+        let frame = DQuat::from_rotation_z(90_f64.to_radians());
+        let plane =
+            HandlePlane::of_handle(GizmoModeOverride::RotateX, DVec3::ONE, frame, DVec3::NEG_Z);
+        assert_vec_eq(plane.origin, DVec3::ONE);
+        assert_parallel(plane.normal, DVec3::Y);
+    }
+
+    #[test]
+    fn plane_handle_plane_is_spanned_by_its_axes() {
+        let plane = HandlePlane::of_handle(
+            GizmoModeOverride::TranslateXZ,
+            DVec3::ZERO,
+            DQuat::IDENTITY,
+            DVec3::NEG_Z,
+        );
+        assert_parallel(plane.normal, DVec3::Y);
+    }
+
+    #[test]
+    fn axis_handle_plane_contains_axis_and_faces_camera() {
+        let view_direction = DVec3::new(0., -1., -1.).normalize();
+        let plane = HandlePlane::of_handle(
+            GizmoModeOverride::ScaleX,
+            DVec3::ZERO,
+            DQuat::IDENTITY,
+            view_direction,
+        );
+        assert!(plane.normal.dot(DVec3::X).abs() < EPSILON);
+        assert_parallel(plane.normal, view_direction);
+    }
+
+    #[test]
+    fn axis_seen_end_on_falls_back_to_facing_plane() {
+        let plane = HandlePlane::of_handle(
+            GizmoModeOverride::TranslateZ,
+            DVec3::ZERO,
+            DQuat::IDENTITY,
+            DVec3::NEG_Z,
+        );
+        assert_parallel(plane.normal, DVec3::Z);
+    }
+
+    #[test]
+    fn view_handles_face_camera() {
+        let view_direction = DVec3::new(1., 0., -1.).normalize();
+        for mode in [
+            GizmoModeOverride::RotateView,
+            GizmoModeOverride::TranslateView,
+            GizmoModeOverride::ScaleUniform,
+        ] {
+            let plane = HandlePlane::of_handle(mode, DVec3::ZERO, DQuat::IDENTITY, view_direction);
+            assert_parallel(plane.normal, view_direction);
+        }
+    }
+
+    #[test]
+    fn intersect_finds_point_in_front_of_ray() {
+        let plane = HandlePlane::facing(DVec3::new(0., 0., -10.), DVec3::NEG_Z);
+        let direction = DVec3::new(1., 0., -1.).normalize();
+        let point = plane.intersect(DVec3::ZERO, direction).unwrap();
+        assert_vec_eq(point, DVec3::new(10., 0., -10.));
+    }
+
+    #[test]
+    fn intersect_ignores_parallel_and_backward_rays() {
+        let plane = HandlePlane::facing(DVec3::new(0., 0., -10.), DVec3::NEG_Z);
+        assert!(plane.intersect(DVec3::ZERO, DVec3::X).is_none());
+        assert!(plane.intersect(DVec3::ZERO, DVec3::Z).is_none());
+    }
+
+    #[test]
+    fn closest_hit_is_the_handle_plane_met_first() {
+        let facing = |depth: f64| HandlePlane::facing(DVec3::new(0., 0., -depth), DVec3::NEG_Z);
+        let hits = [
+            (GizmoModeOverride::RotateView, facing(10.)),
+            (GizmoModeOverride::TranslateX, facing(4.)),
+            (GizmoModeOverride::ScaleX, facing(6.)),
+        ];
+        assert_eq!(
+            closest_hit(hits, DVec3::ZERO, DVec3::NEG_Z),
+            Some(GizmoModeOverride::TranslateX)
+        );
+    }
+
+    #[test]
+    fn missed_planes_come_after_hit_ones() {
+        let behind = HandlePlane::facing(DVec3::new(0., 0., 5.), DVec3::NEG_Z);
+        let edge_on = HandlePlane {
+            origin: DVec3::ZERO,
+            normal: DVec3::X,
+        };
+        let ahead = HandlePlane::facing(DVec3::new(0., 0., -50.), DVec3::NEG_Z);
+        let hits = [
+            (GizmoModeOverride::RotateX, behind),
+            (GizmoModeOverride::RotateY, edge_on),
+            (GizmoModeOverride::RotateZ, ahead),
+        ];
+        assert_eq!(
+            closest_hit(hits, DVec3::ZERO, DVec3::NEG_Z),
+            Some(GizmoModeOverride::RotateZ)
+        );
+        assert_eq!(
+            closest_hit(hits[..2].iter().copied(), DVec3::ZERO, DVec3::NEG_Z),
+            Some(GizmoModeOverride::RotateX)
+        );
+        assert_eq!(
+            closest_hit::<GizmoModeOverride>([], DVec3::ZERO, DVec3::NEG_Z),
+            None
+        );
+    }
+}
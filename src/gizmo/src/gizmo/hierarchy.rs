@@ -0,0 +1,250 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Targets expressed in the space of a parent node.
+//!
+//! The gizmo manipulates world transforms. Targets with a `parentTransform` are converted to world
+//! space before the manipulation, and its result is converted back to their parent space.
+
+use glam::DMat4;
+use transform_gizmo::math::Transform;
+
+fn to_matrix(transform: &Transform) -> DMat4 {
+    DMat4::from_scale_rotation_translation(
+        transform.scale.into(),
+        transform.rotation.into(),
+        transform.translation.into(),
+    )
+}
+
+fn from_matrix(matrix: DMat4) -> Transform {
+    let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+    Transform::from_scale_rotation_translation(scale, rotation.normalize(), translation)
+}
+
+/// World transform of a target with a `local` transform in `parent` space
+///
+/// A non-uniform parent scale shears rotated children, which a `Transform` can't hold. The shear
+/// is dropped here, and restored by [`to_local`].
+pub fn to_world(parent: DMat4, local: &Transform) -> Transform {
+    if parent == DMat4::IDENTITY {
+        return *local;
+    }
+
+    from_matrix(parent * to_matrix(local))
+}
+
+/// Transform in `parent` space of a target moved to `world`, `start_world` and `start_local`
+/// being its transforms before it moved
+///
+/// Only the world motion is brought back to the parent space, so a target that didn't move keeps
+/// its exact local transform.
+pub fn to_local(
+    parent: DMat4,
+    world: &Transform,
+    start_world: &Transform,
+    start_local: &Transform,
+) -> Transform {
+    if parent == DMat4::IDENTITY {
+        return *world;
+    }
+
+    let motion = to_matrix(world) * to_matrix(start_world).inverse();
+    from_matrix(parent.inverse() * motion * parent * to_matrix(start_local))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{DQuat, DVec3};
+
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn transform(scale: DVec3, rotation: DQuat, translation: DVec3) -> Transform {
+        Transform::from_scale_rotation_translation(scale, rotation, translation)
+    }
+
+    fn assert_transform_eq(a: &Transform, b: &Transform) {
+        let (a_scale, b_scale) = (DVec3::from(a.scale), DVec3::from(b.scale));
+        let (a_rotation, b_rotation) = (DQuat::from(a.rotation), DQuat::from(b.rotation));
+        let (a_translation, b_translation) =
+            (DVec3::from(a.translation), DVec3::from(b.translation));
+
+        assert!(
+            a_scale.abs_diff_eq(b_scale, EPSILON),
+            "{a_scale} != {b_scale}"
+        );
+        // `q` and `-q` are the same rotation
+        assert!(
+            a_rotation.dot(b_rotation).abs() > 1. - EPSILON,
+            "{a_rotation} != {b_rotation}"
+        );
+        assert!(
+            a_translation.abs_diff_eq(b_translation, EPSILON),
+            "{a_translation} != {b_translation}"
+        );
+    }
+
+    /// Parent rotated around Y and stretched along its own X axis
+    fn non_uniform_parent() -> DMat4 {
+        DMat4::from_scale_rotation_translation(
+            DVec3::new(2., 1., 0.5),
+            DQuat::from_rotation_y(30_f64.to_radians()),
+            DVec3::new(10., -5., 3.),
+        )
+    }
+
+    #[test]
+    fn without_parent_targets_are_in_world_space() {
+        let target = transform(
+            DVec3::new(1., 2., 3.),
+            DQuat::from_rotation_x(0.3),
+            DVec3::new(4., 5., 6.),
+        );
+
+        assert_transform_eq(&to_world(DMat4::IDENTITY, &target), &target);
+        let moved = transform(DVec3::ONE, DQuat::IDENTITY, DVec3::new(7., 8., 9.));
+        assert_transform_eq(&to_local(DMat4::IDENTITY, &moved, &target, &target), &moved);
+    }
+
+    #[test]
+    fn unmoved_sheared_target_keeps_its_local_transform() {
+        let parent = non_uniform_parent();
+        // Rotated child of a non-uniformly scaled parent, sheared in world space
+        let local = transform(
+            DVec3::new(1., 3., 1.),
+            DQuat::from_rotation_z(45_f64.to_radians()),
+            DVec3::new(1., 2., 3.),
+        );
+
+        let world = to_world(parent, &local);
+        assert_transform_eq(&to_local(parent, &world, &world, &local), &local);
+    }
+
+    #[test]
+    fn world_translation_is_scaled_back_in_parent_space() {
+        let parent = DMat4::from_scale(DVec3::new(2., 1., 4.));
+        let local = transform(DVec3::ONE, DQuat::IDENTITY, DVec3::new(1., 1., 1.));
+
+        let world = to_world(parent, &local);
+        assert_transform_eq(
+            &world,
+            &transform(
+                DVec3::new(2., 1., 4.),
+                DQuat::IDENTITY,
+                DVec3::new(2., 1., 4.),
+            ),
+        );
+
+        let moved = Transform {
+            translation: (DVec3::from(world.translation) + DVec3::new(2., 2., 2.)).into(),
+            ..world
+        };
+        assert_transform_eq(
+            &to_local(parent, &moved, &world, &local),
+            &transform(DVec3::ONE, DQuat::IDENTITY, DVec3::new(2., 3., 1.5)),
+        );
+    }
+
+    #[test]
+    fn world_translation_follows_rotated_non_uniform_parent() {
+        let parent = non_uniform_parent();
+        let local = transform(DVec3::ONE, DQuat::IDENTITY, DVec3::ZERO);
+        let world = to_world(parent, &local);
+
+        // Move by one local unit along the parent X axis, stretched twice in world space
+        let parent_x = DQuat::from_rotation_y(30_f64.to_radians()) * DVec3::X;
+        let moved = Transform {
+            translation: (DVec3::from(world.translation) + parent_x * 2.).into(),
+            ..world
+        };
+
+        assert_transform_eq(
+            &to_local(parent, &moved, &world, &local),
+            &transform(DVec3::ONE, DQuat::IDENTITY, DVec3::X),
+        );
+    }
+
+    #[test]
+    fn world_scale_along_parent_axis_is_kept_in_parent_space() {
+        let parent = non_uniform_parent();
+        let local = transform(
+            DVec3::new(1., 2., 3.),
+            DQuat::IDENTITY,
+            DVec3::new(1., 0., 0.),
+        );
+        let world = to_world(parent, &local);
+
+        // Scale twice along the target axes, which are aligned with the parent ones
+        let moved = Transform {
+            scale: (DVec3::from(world.scale) * DVec3::new(2., 1., 1.)).into(),
+            ..world
+        };
+
+        assert_transform_eq(
+            &to_local(parent, &moved, &world, &local),
+            &transform(
+                DVec3::new(2., 2., 3.),
+                DQuat::IDENTITY,
+                DVec3::new(1., 0., 0.),
+            ),
+        );
+    }
+
+    #[test]
+    fn world_rotation_with_uniform_parent_scale() {
+        let parent = DMat4::from_scale_rotation_translation(
+            DVec3::splat(3.),
+            DQuat::from_rotation_x(90_f64.to_radians()),
+            DVec3::ZERO,
+        );
+        let local = transform(DVec3::ONE, DQuat::IDENTITY, DVec3::ZERO);
+        let world = to_world(parent, &local);
+
+        // Rotating around the world Y axis is rotating around the parent -Z axis
+        let rotation = DQuat::from_rotation_y(0.5);
+        let moved = Transform {
+            rotation: (rotation * DQuat::from(world.rotation)).into(),
+            ..world
+        };
+
+        assert_transform_eq(
+            &to_local(parent, &moved, &world, &local),
+            &transform(DVec3::ONE, DQuat::from_rotation_z(-0.5), DVec3::ZERO),
+        );
+    }
+
+    #[test]
+    fn geospatial_target_round_trips_in_parent_space() {
+        // Parent placed and turned like a local frame on the ground of the Earth
+        let parent = DMat4::from_rotation_translation(
+            DQuat::from_rotation_z(0.3) * DQuat::from_rotation_x(-0.7),
+            DVec3::new(4_205_813.25, 163_892.5, 4_776_431.75),
+        );
+        let local = transform(
+            DVec3::ONE,
+            DQuat::from_rotation_y(0.2),
+            DVec3::new(1234.567891, -12.345678, 0.001),
+        );
+        let world = to_world(parent, &local);
+
+        // Move by one millimetre along the world X axis
+        let moved = Transform {
+            translation: (DVec3::from(world.translation) + DVec3::new(0.001, 0., 0.)).into(),
+            ..world
+        };
+        let moved_local = to_local(parent, &moved, &world, &local);
+
+        let expected = DVec3::from(local.translation)
+            + parent
+                .inverse()
+                .transform_vector3(DVec3::new(0.001, 0., 0.));
+        let translation = DVec3::from(moved_local.translation);
+        assert!(
+            translation.abs_diff_eq(expected, 1e-6),
+            "{translation} != {expected}"
+        );
+        assert_transform_eq(&to_local(parent, &world, &world, &local), &local);
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Feedback drawn with the gizmo while dragging: the sector swept by rotations and the path of
+//! translations, labelled with the angle and the distance.
+
+use super::GizmoRust;
+use crate::overlay::{self, Label, OverlayMesh};
+
+/// Segments of the arc of the rotation sector
+const SECTOR_SEGMENT_COUNT: usize = 48;
+/// Opacity of the sector and of the translation path, relative to the handle color
+const FEEDBACK_ALPHA: f32 = 0.35;
+const TRANSLATION_LINE_WIDTH: f32 = 2.;
+/// Distance of the angle label from the pivot, relative to the sector radius
+const ANGLE_LABEL_DISTANCE: f32 = 1.3;
+
+impl GizmoRust {
+    /// Draw the feedback of the current drag of `targets` seen in a view of `size`, with a rotation
+    /// sector of `radius` pixels, and return its labels
+    pub(super) fn drag_feedback(
+        &self,
+        targets: &[transform_gizmo::math::Transform],
+        size: glam::Vec2,
+        radius: f32,
+        color: [f32; 4],
+        mesh: &mut OverlayMesh,
+    ) -> Vec<Label> {
+        let [red, green, blue, alpha] = color;
+        let color = [red, green, blue, alpha * FEEDBACK_ALPHA];
+
+        let label = if self.active_mode.is_rotate() {
+            self.rotation_feedback(targets, size, radius, color, mesh)
+        } else if self.active_mode.is_translate() {
+            self.translation_feedback(targets, size, color, mesh)
+        } else {
+            None
+        };
+        label.into_iter().collect()
+    }
+
+    fn rotation_feedback(
+        &self,
+        targets: &[transform_gizmo::math::Transform],
+        size: glam::Vec2,
+        radius: f32,
+        color: [f32; 4],
+        mesh: &mut OverlayMesh,
+    ) -> Option<Label> {
+        let camera = self.camera();
+        let to_screen = |point: glam::Vec3| camera.world_to_screen(point, size.x, size.y);
+        let center = camera.to_scene(self.center(targets));
+        let center_on_screen = to_screen(center)?;
+        // Arcball rotations don't have a fixed axis
+        let axis = glam::Vec3::new(
+            self.rotation_axis.x(),
+            self.rotation_axis.y(),
+            self.rotation_axis.z(),
+        )
+        .try_normalize()?;
+        let angle = self.total_rotation_angle;
+
+        // The sector starts where the handle was grabbed, in the rotation plane
+        let (origin, direction) = camera.screen_ray(self.drag_start_cursor, size.x, size.y);
+        let distance = (center - origin).dot(axis) / direction.dot(axis);
+        let start = (origin + direction * distance - center)
+            .try_normalize()
+            .unwrap_or_else(|| axis.any_orthonormal_vector());
+
+        // Keep the sector the same size on screen whatever the distance to the camera
+        let right = camera.rotation * glam::Vec3::X;
+        let pixels_per_unit = to_screen(center + right)?.distance(center_on_screen);
+        let world_radius = radius / pixels_per_unit;
+        if !world_radius.is_finite() {
+            return None;
+        }
+
+        let point = |angle: f32, distance: f32| {
+            to_screen(center + glam::Quat::from_axis_angle(axis, angle) * start * distance)
+        };
+        let rim: Vec<glam::Vec2> = (0..=SECTOR_SEGMENT_COUNT)
+            .filter_map(|i| point(angle * i as f32 / SECTOR_SEGMENT_COUNT as f32, world_radius))
+            .collect();
+        mesh.fan(center_on_screen, &rim, color);
+
+        Some(Label {
+            position: point(angle / 2., world_radius * ANGLE_LABEL_DISTANCE)?,
+            text: format!("{:.1}°", angle.to_degrees()),
+        })
+    }
+
+    fn translation_feedback(
+        &self,
+        targets: &[transform_gizmo::math::Transform],
+        size: glam::Vec2,
+        color: [f32; 4],
+        mesh: &mut OverlayMesh,
+    ) -> Option<Label> {
+        let camera = self.camera();
+        let start = camera.to_scene(self.center(&self.drag_start_transforms));
+        let end = camera.to_scene(self.center(targets));
+        overlay::world_line(
+            mesh,
+            &camera,
+            size,
+            (start, end),
+            TRANSLATION_LINE_WIDTH,
+            (color, color),
+        );
+
+        Some(Label {
+            position: camera.world_to_screen(start.lerp(end, 0.5), size.x, size.y)?,
+            text: format!("{:.2}", start.distance(end)),
+        })
+    }
+}
@@ -0,0 +1,406 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Exact values typed on the keyboard while dragging the gizmo.
+
+use glam::{DQuat, DVec3};
+use transform_gizmo::math::Transform;
+
+use super::{ffi::GizmoModeOverride, pivot};
+
+// Values of `Qt::Key`
+const QT_KEY_ASTERISK: i32 = 0x2a;
+const QT_KEY_COMMA: i32 = 0x2c;
+const QT_KEY_MINUS: i32 = 0x2d;
+const QT_KEY_PERIOD: i32 = 0x2e;
+const QT_KEY_SLASH: i32 = 0x2f;
+const QT_KEY_0: i32 = 0x30;
+const QT_KEY_9: i32 = 0x39;
+
+/// Value typed by the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericValue {
+    /// Exact distance, angle in degrees or scale factor
+    Absolute(f64),
+    /// Multiply the manipulation done with the pointer
+    Multiply(f64),
+    /// Divide the manipulation done with the pointer
+    Divide(f64),
+}
+
+/// Character typed with `key`, if it can be part of a numeric value
+pub fn key_to_char(key: i32) -> Option<char> {
+    match key {
+        QT_KEY_0..=QT_KEY_9 => char::from_u32(key as u32),
+        // Accept both decimal separators
+        QT_KEY_PERIOD | QT_KEY_COMMA => Some('.'),
+        QT_KEY_MINUS => Some('-'),
+        QT_KEY_ASTERISK => Some('*'),
+        QT_KEY_SLASH => Some('/'),
+        _ => None,
+    }
+}
+
+/// Whether `c` can be appended to `text` while keeping a valid numeric input
+///
+/// A divisor starting with `0` can only go on with a decimal separator, so that no digit can make
+/// it zero.
+pub fn accepts(text: &str, c: char) -> bool {
+    let number = text.trim_start_matches(['*', '/']);
+    let zero_divisor = text.starts_with('/') && number.trim_start_matches('-') == "0";
+    match c {
+        '*' | '/' => text.is_empty(),
+        '-' => number.is_empty(),
+        '.' => !number.contains('.'),
+        _ => c.is_ascii_digit() && !zero_divisor,
+    }
+}
+
+/// Parse a complete numeric input such as `2.5`, `-90` or `*2`
+///
+/// A zero divisor, such as `/0.` while typing `/0.5`, is incomplete.
+pub fn parse(text: &str) -> Option<NumericValue> {
+    let (constructor, number): (fn(f64) -> NumericValue, &str) =
+        if let Some(number) = text.strip_prefix('*') {
+            (NumericValue::Multiply, number)
+        } else if let Some(number) = text.strip_prefix('/') {
+            (NumericValue::Divide, number)
+        } else {
+            (NumericValue::Absolute, text)
+        };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .map(constructor)
+        .filter(|value| *value != NumericValue::Divide(0.))
+}
+
+/// Manipulation done with the pointer before typing a value
+#[derive(Debug, Clone, Copy)]
+pub struct PointerManipulation {
+    pub translation: DVec3,
+    pub rotation_axis: DVec3,
+    /// Rotation angle in radians
+    pub rotation_angle: f64,
+    pub scale: DVec3,
+}
+
+impl Default for PointerManipulation {
+    fn default() -> Self {
+        Self {
+            translation: DVec3::ZERO,
+            rotation_axis: DVec3::ZERO,
+            rotation_angle: 0.,
+            scale: DVec3::ONE,
+        }
+    }
+}
+
+/// Everything needed to apply a typed value to the targets of a drag
+pub struct NumericTransform<'a> {
+    /// Mode of the drag
+    pub mode: GizmoModeOverride,
+    /// Orientation of the gizmo axes
+    pub frame: DQuat,
+    /// Camera forward axis, used by view rotations
+    pub view_axis: DVec3,
+    /// Common pivot, or `None` to transform each target around its own origin
+    pub pivot: Option<DVec3>,
+    pub pointer: PointerManipulation,
+    /// Targets when the drag started
+    pub targets: &'a [Transform],
+}
+
+impl NumericTransform<'_> {
+    pub fn apply(&self, value: NumericValue) -> Vec<Transform> {
+        if self.mode.is_translate() {
+            self.translate(value)
+        } else if self.mode.is_rotate() {
+            self.rotate(value)
+        } else if self.mode.is_scale() {
+            self.scale(value)
+        } else {
+            self.targets.to_vec()
+        }
+    }
+
+    /// Axes affected by the mode, in gizmo space
+    fn axis_mask(&self) -> DVec3 {
+        match self.mode {
+            // Like Blender, values typed for a free translation apply along X
+            GizmoModeOverride::TranslateView => DVec3::X,
+            mode => mode.axes(),
+        }
+    }
+
+    fn translate(&self, value: NumericValue) -> Vec<Transform> {
+        let offset = match value {
+            NumericValue::Absolute(distance) => self.frame * (self.axis_mask() * distance),
+            NumericValue::Multiply(factor) => self.pointer.translation * factor,
+            NumericValue::Divide(divisor) => self.pointer.translation / divisor,
+        };
+
+        self.map_targets(|scale, rotation, translation| (scale, rotation, translation + offset))
+    }
+
+    fn rotate(&self, value: NumericValue) -> Vec<Transform> {
+        let mode_axis = if self.mode == GizmoModeOverride::RotateView {
+            self.view_axis
+        } else {
+            self.frame * self.axis_mask()
+        };
+        let pointer_axis = if self.pointer.rotation_axis.length_squared() > 0. {
+            self.pointer.rotation_axis
+        } else {
+            mode_axis
+        };
+
+        let (axis, angle) = match value {
+            NumericValue::Absolute(degrees) => (mode_axis, degrees.to_radians()),
+            NumericValue::Multiply(factor) => (pointer_axis, self.pointer.rotation_angle * factor),
+            NumericValue::Divide(divisor) => (pointer_axis, self.pointer.rotation_angle / divisor),
+        };
+        let delta = DQuat::from_axis_angle(axis.normalize_or(DVec3::X), angle);
+
+        self.map_targets(|scale, rotation, translation| {
+            let translation = match self.pivot {
+                Some(pivot) => pivot + delta * (translation - pivot),
+                None => translation,
+            };
+            (scale, delta * rotation, translation)
+        })
+    }
+
+    fn scale(&self, value: NumericValue) -> Vec<Transform> {
+        let factor = match value {
+            NumericValue::Absolute(factor) => DVec3::ONE + self.axis_mask() * (factor - 1.),
+            NumericValue::Multiply(factor) => self.pointer.scale * factor,
+            NumericValue::Divide(divisor) => self.pointer.scale / divisor,
+        };
+
+        self.map_targets(|scale, rotation, translation| {
+            let translation = match self.pivot {
+                Some(pivot) => {
+                    pivot + self.frame * (factor * (self.frame.inverse() * (translation - pivot)))
+                }
+                None => translation,
+            };
+            (
+                scale * pivot::local_scale(factor, self.frame, rotation),
+                rotation,
+                translation,
+            )
+        })
+    }
+
+    fn map_targets(
+        &self,
+        f: impl Fn(DVec3, DQuat, DVec3) -> (DVec3, DQuat, DVec3),
+    ) -> Vec<Transform> {
+        self.targets
+            .iter()
+            .map(|target| {
+                let (scale, rotation, translation) = f(
+                    target.scale.into(),
+                    target.rotation.into(),
+                    target.translation.into(),
+                );
+                Transform::from_scale_rotation_translation(scale, rotation, translation)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn accepts_all(text: &str) -> bool {
+        let mut typed = String::new();
+        for c in text.chars() {
+            if !accepts(&typed, c) {
+                return false;
+            }
+            typed.push(c);
+        }
+        true
+    }
+
+    fn target(translation: DVec3) -> Transform {
+        Transform::from_scale_rotation_translation(DVec3::ONE, DQuat::IDENTITY, translation)
+    }
+
+    fn numeric_transform(mode: GizmoModeOverride, targets: &[Transform]) -> NumericTransform<'_> {
+        NumericTransform {
+            mode,
+            frame: DQuat::IDENTITY,
+            view_axis: DVec3::NEG_Z,
+            pivot: Some(DVec3::ZERO),
+            pointer: PointerManipulation {
+                translation: DVec3::new(2., 4., 0.),
+                rotation_axis: DVec3::Z,
+                rotation_angle: 90_f64.to_radians(),
+                scale: DVec3::new(2., 1., 1.),
+            },
+            targets,
+        }
+    }
+
+    fn assert_vec_eq(a: impl Into<DVec3>, b: DVec3) {
+        let a = a.into();
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(parse("2.5"), Some(NumericValue::Absolute(2.5)));
+        assert_eq!(parse("-90"), Some(NumericValue::Absolute(-90.)));
+        assert_eq!(parse("*2"), Some(NumericValue::Multiply(2.)));
+        assert_eq!(parse("/0.5"), Some(NumericValue::Divide(0.5)));
+        assert_eq!(parse("/-4"), Some(NumericValue::Divide(-4.)));
+    }
+
+    #[test]
+    fn parse_incomplete_values() {
+        for text in ["", "-", ".", "*", "/", "*-", "/-"] {
+            assert_eq!(parse(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn parse_rejects_zero_divisor() {
+        for text in ["/0", "/0.", "/0.0", "/-0", "/.0"] {
+            assert_eq!(parse(text), None, "{text:?}");
+        }
+        assert_eq!(parse("*0"), Some(NumericValue::Multiply(0.)));
+        assert_eq!(parse("0"), Some(NumericValue::Absolute(0.)));
+    }
+
+    #[test]
+    fn accepts_valid_inputs() {
+        for text in ["12.5", "-90", "*2", "/0.5", "/-0.25", "/10", "0", "*0"] {
+            assert!(accepts_all(text), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn accepts_rejects_invalid_inputs() {
+        for text in ["1*", "2/", "**", "1-", "--1", "1.2.3", "/00", "/05", "/-00"] {
+            assert!(!accepts_all(text), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn absolute_translation_along_axis() {
+        let targets = [target(DVec3::new(1., 0., 0.))];
+        let transforms = numeric_transform(GizmoModeOverride::TranslateY, &targets)
+            .apply(NumericValue::Absolute(3.));
+
+        assert_vec_eq(transforms[0].translation, DVec3::new(1., 3., 0.));
+    }
+
+    #[test]
+    fn absolute_translation_follows_frame() {
+        let targets = [target(DVec3::ZERO)];
+        let transforms = NumericTransform {
+            frame: DQuat::from_rotation_z(90_f64.to_radians()),
+            ..numeric_transform(GizmoModeOverride::TranslateX, &targets)
+        }
+        .apply(NumericValue::Absolute(2.));
+
+        assert_vec_eq(transforms[0].translation, DVec3::new(0., 2., 0.));
+    }
+
+    #[test]
+    fn translation_multiplies_and_divides_pointer() {
+        let targets = [target(DVec3::ZERO)];
+        let numeric = numeric_transform(GizmoModeOverride::TranslateView, &targets);
+
+        let transforms = numeric.apply(NumericValue::Multiply(2.));
+        assert_vec_eq(transforms[0].translation, DVec3::new(4., 8., 0.));
+        let transforms = numeric.apply(NumericValue::Divide(4.));
+        assert_vec_eq(transforms[0].translation, DVec3::new(0.5, 1., 0.));
+    }
+
+    #[test]
+    fn absolute_rotation_around_pivot() {
+        let targets = [target(DVec3::X)];
+        let transforms = numeric_transform(GizmoModeOverride::RotateZ, &targets)
+            .apply(NumericValue::Absolute(90.));
+
+        assert_vec_eq(transforms[0].translation, DVec3::Y);
+        let rotation = DQuat::from(transforms[0].rotation);
+        assert!(rotation.abs_diff_eq(DQuat::from_rotation_z(90_f64.to_radians()), EPSILON));
+    }
+
+    #[test]
+    fn rotation_divides_pointer_angle() {
+        let targets = [target(DVec3::X)];
+        let transforms =
+            numeric_transform(GizmoModeOverride::RotateX, &targets).apply(NumericValue::Divide(2.));
+
+        // Around the pointer axis rather than the mode one
+        let rotation = DQuat::from(transforms[0].rotation);
+        assert!(rotation.abs_diff_eq(DQuat::from_rotation_z(45_f64.to_radians()), EPSILON));
+    }
+
+    #[test]
+    fn absolute_scale_along_axis_around_pivot() {
+        let targets = [target(DVec3::new(1., 1., 0.))];
+        let transforms = numeric_transform(GizmoModeOverride::ScaleX, &targets)
+            .apply(NumericValue::Absolute(3.));
+
+        assert_vec_eq(transforms[0].scale, DVec3::new(3., 1., 1.));
+        assert_vec_eq(transforms[0].translation, DVec3::new(3., 1., 0.));
+    }
+
+    #[test]
+    fn absolute_scale_along_frame_axis_of_rotated_target() {
+        // The target X axis is along the Y axis of the gizmo frame
+        let targets = [Transform::from_scale_rotation_translation(
+            DVec3::ONE,
+            DQuat::from_rotation_z(90_f64.to_radians()),
+            DVec3::ZERO,
+        )];
+        let transforms = numeric_transform(GizmoModeOverride::ScaleX, &targets)
+            .apply(NumericValue::Absolute(3.));
+
+        assert_vec_eq(transforms[0].scale, DVec3::new(1., 3., 1.));
+    }
+
+    #[test]
+    fn scale_without_pivot_keeps_translation() {
+        let targets = [target(DVec3::new(1., 1., 0.))];
+        let transforms = NumericTransform {
+            pivot: None,
+            ..numeric_transform(GizmoModeOverride::ScaleUniform, &targets)
+        }
+        .apply(NumericValue::Multiply(2.));
+
+        assert_vec_eq(transforms[0].scale, DVec3::new(4., 2., 2.));
+        assert_vec_eq(transforms[0].translation, DVec3::new(1., 1., 0.));
+    }
+
+    #[test]
+    fn typed_divisors_keep_transforms_finite() {
+        let targets = [target(DVec3::new(1., 2., 3.))];
+        for text in ["/0.5", "/-0.25", "/3"] {
+            let value = parse(text).unwrap();
+            for mode in [
+                GizmoModeOverride::TranslateView,
+                GizmoModeOverride::RotateView,
+                GizmoModeOverride::ScaleUniform,
+            ] {
+                for transform in numeric_transform(mode, &targets).apply(value) {
+                    assert!(DVec3::from(transform.translation).is_finite(), "{text}");
+                    assert!(DQuat::from(transform.rotation).is_finite(), "{text}");
+                    assert!(DVec3::from(transform.scale).is_finite(), "{text}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Transformations around pivots and along orientations `transform_gizmo` doesn't support.
+//!
+//! The gizmo is given a single proxy target placed on the pivot and oriented along the gizmo
+//! axes, and the totals of its result are applied to the targets as they were when the drag
+//! started.
+
+use glam::{DQuat, DVec3};
+use transform_gizmo::{GizmoResult, math::Transform};
+
+/// Target handed to the gizmo in place of the real targets
+pub fn proxy(pivot: DVec3, frame: DQuat) -> Transform {
+    Transform::from_scale_rotation_translation(DVec3::ONE, frame, pivot)
+}
+
+/// Apply the totals of `result` to the `start` transforms of the drag, around `pivot` or around
+/// each target's own origin when `None`
+pub fn apply(
+    result: &GizmoResult,
+    frame: DQuat,
+    pivot: Option<DVec3>,
+    start: &[Transform],
+) -> Vec<Transform> {
+    let pivot_of = |translation: DVec3| pivot.unwrap_or(translation);
+    let map = |f: &dyn Fn(DVec3, DQuat, DVec3) -> (DVec3, DQuat, DVec3)| -> Vec<Transform> {
+        start
+            .iter()
+            .map(|target| {
+                let (scale, rotation, translation) = f(
+                    target.scale.into(),
+                    target.rotation.into(),
+                    target.translation.into(),
+                );
+                Transform::from_scale_rotation_translation(scale, rotation, translation)
+            })
+            .collect()
+    };
+    let rotate = |delta: DQuat| {
+        map(&|scale, rotation, translation| {
+            let pivot = pivot_of(translation);
+            (
+                scale,
+                delta * rotation,
+                pivot + delta * (translation - pivot),
+            )
+        })
+    };
+
+    match *result {
+        GizmoResult::Translation { total, .. } => {
+            let total = DVec3::from(total);
+            map(&|scale, rotation, translation| (scale, rotation, translation + total))
+        }
+        GizmoResult::Rotation { axis, total, .. } => rotate(DQuat::from_axis_angle(
+            DVec3::from(axis).normalize_or(DVec3::X),
+            total,
+        )),
+        GizmoResult::Arcball { total, .. } => rotate(DQuat::from(total)),
+        GizmoResult::Scale { total } => {
+            let total = DVec3::from(total);
+            map(&|scale, rotation, translation| {
+                let pivot = pivot_of(translation);
+                let offset = frame * (total * (frame.inverse() * (translation - pivot)));
+                (
+                    scale * local_scale(total, frame, rotation),
+                    rotation,
+                    pivot + offset,
+                )
+            })
+        }
+    }
+}
+
+/// Scaling along the own axes of a target oriented by `rotation` matching a scaling by `total`
+/// along the axes of `frame`
+///
+/// They are exact when the target axes are aligned on the frame ones, or when `total` is uniform.
+/// Otherwise the scaling would shear the target, which a scale along its own axes can't express,
+/// and each axis keeps the length the scaling gives it.
+pub fn local_scale(total: DVec3, frame: DQuat, rotation: DQuat) -> DVec3 {
+    let inverse_frame = frame.inverse();
+    DVec3::from_array([DVec3::X, DVec3::Y, DVec3::Z].map(|axis| {
+        let axis = rotation * axis;
+        let scaled = frame * (total * (inverse_frame * axis));
+        // Mirrored along the axis when the scaling flips it
+        scaled.length().copysign(scaled.dot(axis))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn target(rotation: DQuat, translation: DVec3) -> Transform {
+        Transform::from_scale_rotation_translation(DVec3::ONE, rotation, translation)
+    }
+
+    fn assert_vec_eq(a: impl Into<DVec3>, b: DVec3) {
+        let a = a.into();
+        assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+    }
+
+    fn scale(total: DVec3) -> GizmoResult {
+        GizmoResult::Scale {
+            total: total.into(),
+        }
+    }
+
+    #[test]
+    fn translation_moves_all_targets() {
+        let start = [
+            target(DQuat::IDENTITY, DVec3::ZERO),
+            target(DQuat::IDENTITY, DVec3::X),
+        ];
+        let result = GizmoResult::Translation {
+            delta: DVec3::ZERO.into(),
+            total: DVec3::new(1., 2., 3.).into(),
+        };
+        let transforms = apply(&result, DQuat::IDENTITY, None, &start);
+
+        assert_vec_eq(transforms[0].translation, DVec3::new(1., 2., 3.));
+        assert_vec_eq(transforms[1].translation, DVec3::new(2., 2., 3.));
+    }
+
+    #[test]
+    fn rotation_around_pivot_moves_targets() {
+        let start = [target(DQuat::IDENTITY, DVec3::new(2., 0., 0.))];
+        let result = GizmoResult::Rotation {
+            axis: DVec3::Z.into(),
+            delta: 0.,
+            total: 90_f64.to_radians(),
+            is_view_axis: false,
+        };
+        let transforms = apply(&result, DQuat::IDENTITY, Some(DVec3::X), &start);
+
+        assert_vec_eq(transforms[0].translation, DVec3::new(1., 1., 0.));
+        let rotation = DQuat::from(transforms[0].rotation);
+        assert!(rotation.abs_diff_eq(DQuat::from_rotation_z(90_f64.to_radians()), EPSILON));
+    }
+
+    #[test]
+    fn rotation_around_own_origin_keeps_translation() {
+        let start = [target(DQuat::IDENTITY, DVec3::new(2., 0., 0.))];
+        let result = GizmoResult::Rotation {
+            axis: DVec3::Z.into(),
+            delta: 0.,
+            total: 90_f64.to_radians(),
+            is_view_axis: false,
+        };
+        let transforms = apply(&result, DQuat::IDENTITY, None, &start);
+
+        assert_vec_eq(transforms[0].translation, DVec3::new(2., 0., 0.));
+    }
+
+    #[test]
+    fn scale_around_pivot_moves_targets_along_frame() {
+        let frame = DQuat::from_rotation_z(90_f64.to_radians());
+        let start = [target(frame, DVec3::new(0., 1., 0.))];
+        // Frame X is world Y
+        let transforms = apply(
+            &scale(DVec3::new(2., 1., 1.)),
+            frame,
+            Some(DVec3::ZERO),
+            &start,
+        );
+
+        assert_vec_eq(transforms[0].translation, DVec3::new(0., 2., 0.));
+        assert_vec_eq(transforms[0].scale, DVec3::new(2., 1., 1.));
+    }
+
+    #[test]
+    fn scale_in_frame_is_converted_to_target_axes() {
+        // Target X axis along world Y, Y axis along world -X
+        let start = [target(
+            DQuat::from_rotation_z(90_f64.to_radians()),
+            DVec3::ZERO,
+        )];
+        let transforms = apply(
+            &scale(DVec3::new(2., 3., 1.)),
+            DQuat::IDENTITY,
+            None,
+            &start,
+        );
+
+        assert_vec_eq(transforms[0].scale, DVec3::new(3., 2., 1.));
+    }
+
+    #[test]
+    fn uniform_scale_ignores_target_rotation() {
+        let rotation = DQuat::from_euler(glam::EulerRot::YXZ, 0.3, 0.7, -1.1);
+        let start = [target(rotation, DVec3::ZERO)];
+        let transforms = apply(&scale(DVec3::splat(2.)), DQuat::IDENTITY, None, &start);
+
+        assert_vec_eq(transforms[0].scale, DVec3::splat(2.));
+    }
+
+    #[test]
+    fn mirroring_scale_keeps_its_sign() {
+        let start = [target(
+            DQuat::from_rotation_z(90_f64.to_radians()),
+            DVec3::ZERO,
+        )];
+        let transforms = apply(
+            &scale(DVec3::new(-1., 1., 1.)),
+            DQuat::IDENTITY,
+            None,
+            &start,
+        );
+
+        assert_vec_eq(transforms[0].scale, DVec3::new(1., -1., 1.));
+    }
+
+    #[test]
+    fn sheared_scale_keeps_stretched_axis_lengths() {
+        // Target axes 45° off the frame ones can't follow the scale exactly
+        let start = [target(
+            DQuat::from_rotation_z(45_f64.to_radians()),
+            DVec3::ZERO,
+        )];
+        let transforms = apply(
+            &scale(DVec3::new(2., 1., 1.)),
+            DQuat::IDENTITY,
+            None,
+            &start,
+        );
+
+        let stretched = (DVec3::new(2., 1., 0.) * 0.5_f64.sqrt()).length();
+        assert_vec_eq(transforms[0].scale, DVec3::new(stretched, stretched, 1.));
+    }
+}
@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Euler angles of the targets rotations.
+//!
+//! Euler angles are given as a `vector3d` of the angles about the X, Y and Z axes in degrees, like
+//! the `eulerRotation` of QtQuick3D nodes. The `rotationFormat` only changes the order they are
+//! applied in.
+
+use glam::{DQuat, EulerRot};
+
+use super::RotationFormat;
+
+impl RotationFormat {
+    /// Order of the Euler angles written in `transformUpdated`, `None` to write quaternions
+    pub fn euler_order(self) -> Option<EulerRot> {
+        match self {
+            Self::EulerXYZ => Some(EulerRot::XYZ),
+            Self::EulerXZY => Some(EulerRot::XZY),
+            Self::EulerYXZ => Some(EulerRot::YXZ),
+            Self::EulerYZX => Some(EulerRot::YZX),
+            Self::EulerZXY => Some(EulerRot::ZXY),
+            Self::EulerZYX => Some(EulerRot::ZYX),
+            _ => None,
+        }
+    }
+
+    /// Order of the Euler angles read from `targets`. Quaternion formats still accept Euler angles,
+    /// applied in the order of QtQuick3D `eulerRotation`.
+    pub fn input_euler_order(self) -> EulerRot {
+        self.euler_order().unwrap_or(EulerRot::YXZ)
+    }
+}
+
+/// Rotation of the `angles`, in degrees about X, Y and Z, applied in `order`
+pub fn from_euler(order: EulerRot, angles: [f64; 3]) -> DQuat {
+    let [a, b, c] = axes(order).map(|axis| angles[axis].to_radians());
+    DQuat::from_euler(order, a, b, c)
+}
+
+/// Angles, in degrees about X, Y and Z, of `rotation` applied in `order`
+pub fn to_euler(order: EulerRot, rotation: DQuat) -> [f64; 3] {
+    let (a, b, c) = rotation.normalize().to_euler(order);
+    let mut angles = [0.; 3];
+    for (axis, angle) in axes(order).into_iter().zip([a, b, c]) {
+        angles[axis] = angle.to_degrees();
+    }
+    angles
+}
+
+/// Indices of the axes rotated about by `order`, first rotation first
+fn axes(order: EulerRot) -> [usize; 3] {
+    match order {
+        EulerRot::XYZ => [0, 1, 2],
+        EulerRot::XZY => [0, 2, 1],
+        EulerRot::YXZ => [1, 0, 2],
+        EulerRot::YZX => [1, 2, 0],
+        EulerRot::ZXY => [2, 0, 1],
+        EulerRot::ZYX => [2, 1, 0],
+        _ => unreachable!("Only intrinsic three-axis orders are used"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: [EulerRot; 6] = [
+        EulerRot::XYZ,
+        EulerRot::XZY,
+        EulerRot::YXZ,
+        EulerRot::YZX,
+        EulerRot::ZXY,
+        EulerRot::ZYX,
+    ];
+
+    /// `QQuaternion::fromEulerAngles`, which `Qt.quaternion.fromEulerAngles` and the
+    /// `eulerRotation` of QtQuick3D nodes use
+    fn qt_from_euler_angles([pitch, yaw, roll]: [f64; 3]) -> DQuat {
+        let (pitch, yaw, roll) = (
+            pitch.to_radians() * 0.5,
+            yaw.to_radians() * 0.5,
+            roll.to_radians() * 0.5,
+        );
+        let (c1, s1) = (yaw.cos(), yaw.sin());
+        let (c2, s2) = (roll.cos(), roll.sin());
+        let (c3, s3) = (pitch.cos(), pitch.sin());
+        let (c1c2, s1s2) = (c1 * c2, s1 * s2);
+        DQuat::from_xyzw(
+            c1c2 * s3 + s1s2 * c3,
+            s1 * c2 * c3 - c1 * s2 * s3,
+            c1 * s2 * c3 - s1 * c2 * s3,
+            c1c2 * c3 + s1s2 * s3,
+        )
+    }
+
+    #[test]
+    fn angles_round_trip_in_every_order() {
+        let angles = [25., -40., 70.];
+        for order in ORDERS {
+            let rotation = from_euler(order, angles);
+            let round_trip = to_euler(order, rotation);
+            for axis in 0..3 {
+                assert!(
+                    (round_trip[axis] - angles[axis]).abs() < 1e-9,
+                    "{order:?}: {round_trip:?} != {angles:?}"
+                );
+            }
+            assert!(
+                from_euler(order, round_trip).abs_diff_eq(rotation, 1e-12),
+                "{order:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn orders_apply_the_axes_in_sequence() {
+        let angles = [25., -40., 70.];
+        let [x, y, z] = angles.map(f64::to_radians);
+        let (x, y, z) = (
+            DQuat::from_rotation_x(x),
+            DQuat::from_rotation_y(y),
+            DQuat::from_rotation_z(z),
+        );
+        for (order, expected) in ORDERS.into_iter().zip([
+            x * y * z,
+            x * z * y,
+            y * x * z,
+            y * z * x,
+            z * x * y,
+            z * y * x,
+        ]) {
+            let rotation = from_euler(order, angles);
+            assert!(
+                rotation.abs_diff_eq(expected, 1e-12),
+                "{order:?}: {rotation} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn yxz_matches_qt_from_euler_angles() {
+        for angles in [[25., -40., 70.], [90., 0., 0.], [-170., 135., 10.]] {
+            let rotation = from_euler(EulerRot::YXZ, angles);
+            let expected = qt_from_euler_angles(angles);
+            assert!(
+                rotation.abs_diff_eq(expected, 1e-12),
+                "{angles:?}: {rotation} != {expected}"
+            );
+        }
+        assert_eq!(
+            RotationFormat::Quaternion.input_euler_order(),
+            EulerRot::YXZ
+        );
+    }
+}
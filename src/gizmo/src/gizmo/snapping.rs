@@ -0,0 +1,328 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Snapping of the gizmo results to absolute increments and to points of the scene.
+//!
+//! `transform_gizmo` only snaps the manipulation relatively to where the drag started. Here the
+//! active target (the last one) is rounded to absolute multiples of the increments, or moved onto
+//! a point of the scene, and the other targets follow it so the selection keeps its layout.
+
+use glam::{DQuat, DVec3};
+use transform_gizmo::{GizmoResult, math::Transform};
+
+/// Components smaller than this are considered unchanged by the drag
+const EPSILON: f64 = 1e-6;
+
+pub struct AbsoluteGrid {
+    /// Grid spacing of positions
+    pub distance: f64,
+    /// Angle increment, in radians
+    pub angle: f64,
+    /// Scale increment
+    pub scale: f64,
+    /// Orientation of the gizmo axes, the grid is aligned on them
+    pub frame: DQuat,
+    /// Axes of `frame` the drag is constrained to, 1 for free axes and 0 for locked ones
+    pub axes: DVec3,
+    /// Common pivot, or `None` when each target is transformed around its own origin
+    pub pivot: Option<DVec3>,
+}
+
+impl AbsoluteGrid {
+    /// Snap `transforms` computed by `transform_gizmo` from the `start` transforms of the drag
+    pub fn snap(
+        &self,
+        result: &GizmoResult,
+        start: &[Transform],
+        transforms: &[Transform],
+    ) -> Vec<Transform> {
+        match *result {
+            GizmoResult::Translation { .. } => self.snap_translation(start, transforms),
+            GizmoResult::Rotation { axis, .. } => self.snap_rotation(axis.into(), transforms),
+            GizmoResult::Scale { .. } => self.snap_scale(start, transforms),
+            GizmoResult::Arcball { .. } => transforms.to_vec(),
+        }
+    }
+
+    fn snap_translation(&self, start: &[Transform], transforms: &[Transform]) -> Vec<Transform> {
+        let (Some(active_start), Some(active)) = (start.last(), transforms.last()) else {
+            return transforms.to_vec();
+        };
+
+        // Only round the axes of the drag frame the drag is allowed to and did move along
+        let inverse_frame = self.frame.inverse();
+        let start_position = inverse_frame * DVec3::from(active_start.translation);
+        let position = inverse_frame * DVec3::from(active.translation);
+        let moved = (position - start_position)
+            .abs()
+            .cmpgt(DVec3::splat(EPSILON))
+            & self.axes.cmpgt(DVec3::ZERO);
+        let snapped = round_to(position, DVec3::splat(self.distance));
+        let offset = self.frame * DVec3::select(moved, snapped - position, DVec3::ZERO);
+
+        transforms
+            .iter()
+            .map(|transform| Transform {
+                translation: (DVec3::from(transform.translation) + offset).into(),
+                ..*transform
+            })
+            .collect()
+    }
+
+    fn snap_rotation(&self, axis: DVec3, transforms: &[Transform]) -> Vec<Transform> {
+        let axis = axis.normalize_or_zero();
+        let Some(active) = transforms.last() else {
+            return transforms.to_vec();
+        };
+        if axis == DVec3::ZERO || self.angle <= 0. {
+            return transforms.to_vec();
+        }
+
+        let correction = |rotation: DQuat| {
+            let angle = twist_angle(rotation, axis);
+            let snapped = (angle / self.angle).round() * self.angle;
+            DQuat::from_axis_angle(axis, snapped - angle)
+        };
+        let active_correction = correction(active.rotation.into());
+
+        transforms
+            .iter()
+            .map(|transform| {
+                let rotation = DQuat::from(transform.rotation);
+                let translation = DVec3::from(transform.translation);
+                let (rotation, translation) = match self.pivot {
+                    Some(pivot) => (
+                        active_correction * rotation,
+                        pivot + active_correction * (translation - pivot),
+                    ),
+                    None => (correction(rotation) * rotation, translation),
+                };
+                Transform {
+                    rotation: rotation.into(),
+                    translation: translation.into(),
+                    ..*transform
+                }
+            })
+            .collect()
+    }
+
+    fn snap_scale(&self, start: &[Transform], transforms: &[Transform]) -> Vec<Transform> {
+        let (Some(active_start), Some(active)) = (start.last(), transforms.last()) else {
+            return transforms.to_vec();
+        };
+        if self.scale <= 0. {
+            return transforms.to_vec();
+        }
+
+        let start_scale = DVec3::from(active_start.scale);
+        let scale = DVec3::from(active.scale);
+        let scaled = (scale - start_scale).abs().cmpgt(DVec3::splat(EPSILON));
+        // Never snap to a null scale
+        let snapped = round_to(scale, DVec3::splat(self.scale)).max(DVec3::splat(self.scale));
+        let ratio = snapped / scale;
+        let ratio = DVec3::select(scaled & ratio.is_finite_mask(), ratio, DVec3::ONE);
+
+        transforms
+            .iter()
+            .map(|transform| {
+                let translation = DVec3::from(transform.translation);
+                let translation = match self.pivot {
+                    Some(pivot) => {
+                        pivot
+                            + self.frame * (ratio * (self.frame.inverse() * (translation - pivot)))
+                    }
+                    None => translation,
+                };
+                Transform {
+                    scale: (DVec3::from(transform.scale) * ratio).into(),
+                    translation: translation.into(),
+                    ..*transform
+                }
+            })
+            .collect()
+    }
+}
+
+/// Move the active target (the last one) onto `point`, only along the `axes` of `frame` allowed by
+/// the drag, the other targets following it
+pub fn snap_to_point(
+    point: DVec3,
+    frame: DQuat,
+    axes: DVec3,
+    transforms: &[Transform],
+) -> Vec<Transform> {
+    let Some(active) = transforms.last() else {
+        return transforms.to_vec();
+    };
+
+    let offset = point - DVec3::from(active.translation);
+    let offset = frame * (axes * (frame.inverse() * offset));
+
+    transforms
+        .iter()
+        .map(|transform| Transform {
+            translation: (DVec3::from(transform.translation) + offset).into(),
+            ..*transform
+        })
+        .collect()
+}
+
+fn round_to(value: DVec3, increment: DVec3) -> DVec3 {
+    let rounded = (value / increment).round() * increment;
+    DVec3::select(increment.cmpgt(DVec3::ZERO), rounded, value)
+}
+
+/// Angle of the rotation around `axis`, from the swing-twist decomposition of `rotation`
+fn twist_angle(rotation: DQuat, axis: DVec3) -> f64 {
+    2. * rotation.xyz().dot(axis).atan2(rotation.w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn at(translation: DVec3) -> Transform {
+        Transform::from_scale_rotation_translation(DVec3::ONE, DQuat::IDENTITY, translation)
+    }
+
+    fn grid(frame: DQuat, axes: DVec3) -> AbsoluteGrid {
+        AbsoluteGrid {
+            distance: 1.,
+            angle: 15_f64.to_radians(),
+            scale: 0.5,
+            frame,
+            axes,
+            pivot: None,
+        }
+    }
+
+    fn translation() -> GizmoResult {
+        GizmoResult::Translation {
+            delta: DVec3::ZERO.into(),
+            total: DVec3::ZERO.into(),
+        }
+    }
+
+    fn assert_translation_eq(transform: &Transform, expected: DVec3) {
+        let translation = DVec3::from(transform.translation);
+        assert!(
+            translation.abs_diff_eq(expected, EPSILON),
+            "{translation} != {expected}"
+        );
+    }
+
+    #[test]
+    fn translation_rounds_moved_axis() {
+        let start = [at(DVec3::new(0.2, 0.3, 0.))];
+        let moved = [at(DVec3::new(1.3, 0.3, 0.))];
+        let snapped = grid(DQuat::IDENTITY, DVec3::X).snap(&translation(), &start, &moved);
+        assert_translation_eq(&snapped[0], DVec3::new(1., 0.3, 0.));
+    }
+
+    #[test]
+    fn translation_rounds_in_drag_frame() {
+        let frame = DQuat::from_rotation_z(45_f64.to_radians());
+        let start = [at(DVec3::ZERO)];
+        let moved = [at(frame * DVec3::new(2.3, 0., 0.))];
+        let snapped = grid(frame, DVec3::X).snap(&translation(), &start, &moved);
+        assert_translation_eq(&snapped[0], frame * DVec3::new(2., 0., 0.));
+    }
+
+    #[test]
+    fn translation_keeps_unconstrained_axes() {
+        // Drift along Y, e.g. from float errors, isn't snapped by a drag along X
+        let start = [at(DVec3::new(0., 0.3, 0.))];
+        let moved = [at(DVec3::new(1.2, 0.301, 0.))];
+        let snapped = grid(DQuat::IDENTITY, DVec3::X).snap(&translation(), &start, &moved);
+        assert_translation_eq(&snapped[0], DVec3::new(1., 0.301, 0.));
+    }
+
+    #[test]
+    fn translation_moves_other_targets_with_active_one() {
+        let start = [at(DVec3::new(5., 0., 0.)), at(DVec3::ZERO)];
+        let moved = [at(DVec3::new(5., 1.4, 0.)), at(DVec3::new(0., 1.4, 0.))];
+        let snapped = grid(DQuat::IDENTITY, DVec3::Y).snap(&translation(), &start, &moved);
+        assert_translation_eq(&snapped[0], DVec3::new(5., 1., 0.));
+        assert_translation_eq(&snapped[1], DVec3::new(0., 1., 0.));
+    }
+
+    #[test]
+    fn rotation_rounds_angle_around_axis() {
+        let moved = [Transform::from_scale_rotation_translation(
+            DVec3::ONE,
+            DQuat::from_rotation_z(20_f64.to_radians()),
+            DVec3::ZERO,
+        )];
+        let result = GizmoResult::Rotation {
+            axis: DVec3::Z.into(),
+            delta: 0.,
+            total: 0.,
+            is_view_axis: false,
+        };
+        let snapped = grid(DQuat::IDENTITY, DVec3::Z).snap(&result, &moved, &moved);
+        let expected = DQuat::from_rotation_z(15_f64.to_radians());
+        let rotation = DQuat::from(snapped[0].rotation);
+        assert!(
+            rotation.dot(expected).abs() > 1. - EPSILON,
+            "{rotation} != {expected}"
+        );
+    }
+
+    #[test]
+    fn rotation_around_pivot_moves_targets() {
+        let pivot = DVec3::ZERO;
+        let rotation = DQuat::from_rotation_z(20_f64.to_radians());
+        let moved = [Transform::from_scale_rotation_translation(
+            DVec3::ONE,
+            rotation,
+            rotation * DVec3::X,
+        )];
+        let result = GizmoResult::Rotation {
+            axis: DVec3::Z.into(),
+            delta: 0.,
+            total: 0.,
+            is_view_axis: false,
+        };
+        let grid = AbsoluteGrid {
+            pivot: Some(pivot),
+            ..grid(DQuat::IDENTITY, DVec3::Z)
+        };
+        let snapped = grid.snap(&result, &moved, &moved);
+        assert_translation_eq(
+            &snapped[0],
+            DQuat::from_rotation_z(15_f64.to_radians()) * DVec3::X,
+        );
+    }
+
+    #[test]
+    fn scale_rounds_and_never_reaches_zero() {
+        let start = [at(DVec3::ZERO)];
+        let moved = [Transform::from_scale_rotation_translation(
+            DVec3::new(1.3, 0.1, 1.),
+            DQuat::IDENTITY,
+            DVec3::ZERO,
+        )];
+        let result = GizmoResult::Scale {
+            total: DVec3::ONE.into(),
+        };
+        let snapped = grid(DQuat::IDENTITY, DVec3::ONE).snap(&result, &start, &moved);
+        let scale = DVec3::from(snapped[0].scale);
+        let expected = DVec3::new(1.5, 0.5, 1.);
+        assert!(
+            scale.abs_diff_eq(expected, EPSILON),
+            "{scale} != {expected}"
+        );
+    }
+
+    #[test]
+    fn snap_to_point_moves_along_allowed_axes() {
+        let frame = DQuat::from_rotation_z(90_f64.to_radians());
+        let transforms = [at(DVec3::new(3., 0., 0.)), at(DVec3::ZERO)];
+        // Frame X is world Y
+        let snapped = snap_to_point(DVec3::new(2., 5., 7.), frame, DVec3::X, &transforms);
+        assert_translation_eq(&snapped[0], DVec3::new(3., 5., 0.));
+        assert_translation_eq(&snapped[1], DVec3::new(0., 5., 0.));
+    }
+}
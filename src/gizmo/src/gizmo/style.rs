@@ -0,0 +1,169 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Mapping of the gizmo visuals, from a `GizmoStyle` or the gizmo own properties, to
+//! `transform_gizmo::GizmoVisuals`.
+
+use cxx_qt_lib::QColor;
+
+use super::{GizmoModeOverride, GizmoRust};
+use crate::{gizmo_style::ffi::GizmoStyle, overlay};
+
+const DEFAULT_INACTIVE_ALPHA: f32 = 0.7;
+const DEFAULT_HIGHLIGHT_ALPHA: f32 = 1.0;
+const DEFAULT_STROKE_WIDTH: f32 = 4.0;
+const DEFAULT_GIZMO_SIZE: f32 = 75.0;
+
+pub struct Style {
+    /// Colors of the X, Y, Z and view handles
+    colors: [QColor; 4],
+    /// Colors of the hovered or dragged X, Y, Z and view handles, invalid to keep the handle color
+    hover_colors: [QColor; 4],
+    inactive_alpha: f32,
+    highlight_alpha: f32,
+    stroke_width: f32,
+    /// Sizes of a gizmo showing translation, rotation or scale handles only
+    sizes: [f32; 3],
+}
+
+impl From<&GizmoStyle> for Style {
+    fn from(style: &GizmoStyle) -> Self {
+        Self {
+            colors: [
+                style.x_color().clone(),
+                style.y_color().clone(),
+                style.z_color().clone(),
+                style.s_color().clone(),
+            ],
+            hover_colors: [
+                style.x_hover_color().clone(),
+                style.y_hover_color().clone(),
+                style.z_hover_color().clone(),
+                style.s_hover_color().clone(),
+            ],
+            inactive_alpha: *style.inactive_alpha(),
+            highlight_alpha: *style.highlight_alpha(),
+            stroke_width: *style.stroke_width(),
+            sizes: [
+                *style.translate_gizmo_size(),
+                *style.rotate_gizmo_size(),
+                *style.scale_gizmo_size(),
+            ],
+        }
+    }
+}
+
+impl From<&GizmoRust> for Style {
+    fn from(gizmo: &GizmoRust) -> Self {
+        Self {
+            colors: [
+                gizmo.x_color.clone(),
+                gizmo.y_color.clone(),
+                gizmo.z_color.clone(),
+                gizmo.s_color.clone(),
+            ],
+            hover_colors: Default::default(),
+            inactive_alpha: gizmo.inactive_alpha,
+            highlight_alpha: gizmo.highlight_alpha,
+            stroke_width: gizmo.stroke_width,
+            sizes: [gizmo.gizmo_size; 3],
+        }
+    }
+}
+
+impl Style {
+    /// Visuals of a gizmo showing `modes`, `focused` being the hovered or dragged mode
+    pub fn visuals(
+        &self,
+        modes: transform_gizmo::EnumSet<transform_gizmo::GizmoMode>,
+        focused: GizmoModeOverride,
+    ) -> transform_gizmo::GizmoVisuals {
+        let [x_color, y_color, z_color, s_color] = self.colors.each_ref().map(color32);
+
+        transform_gizmo::GizmoVisuals {
+            x_color,
+            y_color,
+            z_color,
+            s_color,
+            inactive_alpha: finite_or(self.inactive_alpha, DEFAULT_INACTIVE_ALPHA),
+            highlight_alpha: finite_or(self.highlight_alpha, DEFAULT_HIGHLIGHT_ALPHA),
+            highlight_color: handle_axis(focused)
+                .map(|axis| &self.hover_colors[axis])
+                .filter(|color| color.is_valid())
+                .map(color32),
+            stroke_width: finite_or(self.stroke_width, DEFAULT_STROKE_WIDTH),
+            gizmo_size: self.size(modes),
+        }
+    }
+
+    /// Color of the handle of `mode`, the view handles color for `NoOverride`
+    pub fn handle_color(&self, mode: GizmoModeOverride) -> [f32; 4] {
+        overlay::rgba(&self.colors[handle_axis(mode).unwrap_or(3)])
+    }
+
+    /// Size of a gizmo showing `modes`
+    ///
+    /// `transform_gizmo` lays all the handles out from a single size, so the largest size of the
+    /// kinds of handles shown is used when they are mixed.
+    fn size(&self, modes: transform_gizmo::EnumSet<transform_gizmo::GizmoMode>) -> f32 {
+        let shows =
+            |is_kind: fn(GizmoModeOverride) -> bool| modes.iter().any(|mode| is_kind(mode.into()));
+        let kinds = [
+            shows(GizmoModeOverride::is_translate),
+            shows(GizmoModeOverride::is_rotate),
+            shows(GizmoModeOverride::is_scale),
+        ];
+
+        kinds
+            .into_iter()
+            .zip(self.sizes)
+            .filter_map(|(shown, size)| shown.then(|| finite_or(size, DEFAULT_GIZMO_SIZE)))
+            .reduce(f32::max)
+            .unwrap_or(DEFAULT_GIZMO_SIZE)
+    }
+}
+
+/// Index in `Style::colors` of the color `transform_gizmo` draws the handle of `mode` with, `None`
+/// for `NoOverride`
+///
+/// Plane handles take the color of their normal.
+fn handle_axis(mode: GizmoModeOverride) -> Option<usize> {
+    match mode {
+        GizmoModeOverride::NoOverride => None,
+        GizmoModeOverride::TranslateX
+        | GizmoModeOverride::RotateX
+        | GizmoModeOverride::ScaleX
+        | GizmoModeOverride::TranslateYZ
+        | GizmoModeOverride::ScaleYZ => Some(0),
+        GizmoModeOverride::TranslateY
+        | GizmoModeOverride::RotateY
+        | GizmoModeOverride::ScaleY
+        | GizmoModeOverride::TranslateXZ
+        | GizmoModeOverride::ScaleXZ => Some(1),
+        GizmoModeOverride::TranslateZ
+        | GizmoModeOverride::RotateZ
+        | GizmoModeOverride::ScaleZ
+        | GizmoModeOverride::TranslateXY
+        | GizmoModeOverride::ScaleXY => Some(2),
+        _ => Some(3),
+    }
+}
+
+/// `value` without its sign, `default` when it isn't finite
+fn finite_or(value: f32, default: f32) -> f32 {
+    if value.is_finite() {
+        value.abs()
+    } else {
+        default
+    }
+}
+
+/// `color` with its alpha, which `transform_gizmo` multiplies by the inactive or highlight alpha
+fn color32(color: &QColor) -> transform_gizmo::Color32 {
+    transform_gizmo::Color32::from_rgba_unmultiplied(
+        color.red() as u8,
+        color.green() as u8,
+        color.blue() as u8,
+        color.alpha() as u8,
+    )
+}
@@ -0,0 +1,223 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Targets parsed from the `targets` property.
+//!
+//! Going through the `QVariantList` of `QVariantMap` is slow for large selections, so it is only
+//! done when `targets` changes, instead of on every hover move and repaint.
+
+use cxx_qt_lib::{QVariant, QVector3D};
+use glam::{BVec3, DMat4};
+use transform_gizmo::math::Transform;
+
+use super::{RotationFormat, constraints::Constraints, ffi, hierarchy, rotation_format};
+
+#[derive(Default)]
+pub struct Targets {
+    /// Transforms as given, in the space of their `parentTransform`
+    local: Vec<Transform>,
+    /// Transforms in world space
+    world: Vec<Transform>,
+    /// World transforms of the parents
+    parents: Vec<DMat4>,
+    /// Constraints given by the targets, without the gizmo ones
+    constraints: Vec<Constraints>,
+}
+
+impl Targets {
+    /// Parse `targets`, reading their Euler angles in the order of `rotation_format`
+    pub fn parse(targets: &QVariant, rotation_format: RotationFormat) -> Self {
+        let target_count = ffi::extract_target_count_from_qvariant(targets);
+
+        let mut positions = vec![[0.; 3]; target_count];
+        let mut rotations = vec![[0.; 4]; target_count];
+        let mut eulers = vec![false; target_count];
+        let mut scales = vec![[0.; 3]; target_count];
+        ffi::extract_targets_from_qvariant(
+            targets,
+            &mut positions,
+            &mut rotations,
+            &mut eulers,
+            &mut scales,
+        );
+        let euler_order = rotation_format.input_euler_order();
+        let local = itertools::multizip((positions, rotations, eulers, scales))
+            .map(|(position, rotation, euler, scale)| {
+                let rotation = if euler {
+                    let [x, y, z, _] = rotation;
+                    rotation_format::from_euler(euler_order, [x, y, z])
+                } else {
+                    glam::DQuat::from_array(rotation)
+                };
+                Transform::from_scale_rotation_translation(
+                    glam::DVec3::from_array(scale),
+                    rotation,
+                    glam::DVec3::from_array(position),
+                )
+            })
+            .collect();
+
+        let mut parents = vec![[0.; 16]; target_count];
+        ffi::extract_target_parents_from_qvariant(targets, &mut parents);
+        let parents = parents.iter().map(DMat4::from_cols_array).collect();
+
+        let mut locked_translations = vec![[false; 3]; target_count];
+        let mut allowed_rotations = vec![[true; 3]; target_count];
+        let mut min_positions = vec![[0.; 3]; target_count];
+        let mut max_positions = vec![[0.; 3]; target_count];
+        let mut min_scales = vec![QVector3D::default(); target_count];
+        let mut max_scales = vec![QVector3D::default(); target_count];
+        ffi::extract_target_constraints_from_qvariant(
+            targets,
+            &mut locked_translations,
+            &mut allowed_rotations,
+            &mut min_positions,
+            &mut max_positions,
+            &mut min_scales,
+            &mut max_scales,
+        );
+        let constraints = itertools::multizip((
+            locked_translations,
+            allowed_rotations,
+            min_positions,
+            max_positions,
+            min_scales,
+            max_scales,
+        ))
+        .map(
+            |(
+                locked_translation,
+                allowed_rotation,
+                min_position,
+                max_position,
+                min_scale,
+                max_scale,
+            )| {
+                Constraints {
+                    locked_translation: BVec3::from_array(locked_translation),
+                    allowed_rotation: BVec3::from_array(allowed_rotation),
+                    min_position: glam::DVec3::from_array(min_position),
+                    max_position: glam::DVec3::from_array(max_position),
+                    min_scale: to_dvec3(&min_scale),
+                    max_scale: to_dvec3(&max_scale),
+                }
+            },
+        )
+        .collect();
+
+        let mut this = Self {
+            parents,
+            constraints,
+            ..Default::default()
+        };
+        this.set_local(local);
+        this
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.local.is_empty()
+    }
+
+    pub fn local(&self) -> &[Transform] {
+        &self.local
+    }
+
+    pub fn world(&self) -> &[Transform] {
+        &self.world
+    }
+
+    pub fn parents(&self) -> &[DMat4] {
+        &self.parents
+    }
+
+    pub fn constraints(&self) -> &[Constraints] {
+        &self.constraints
+    }
+
+    /// Replace the transforms, in parent space, when the gizmo writes the targets back. Parents
+    /// and bounds are kept.
+    pub fn set_local(&mut self, local: Vec<Transform>) {
+        self.world = local
+            .iter()
+            .zip(&self.parents)
+            .map(|(local, parent)| hierarchy::to_world(*parent, local))
+            .collect();
+        self.local = local;
+    }
+}
+
+fn to_dvec3(v: &QVector3D) -> glam::DVec3 {
+    glam::Vec3::new(v.x(), v.y(), v.z()).as_dvec3()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::{DQuat, DVec3};
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_translations_eq(transforms: &[Transform], expected: &[DVec3]) {
+        assert_eq!(transforms.len(), expected.len());
+        for (transform, expected) in transforms.iter().zip(expected) {
+            let translation = DVec3::from(transform.translation);
+            assert!(
+                translation.abs_diff_eq(*expected, EPSILON),
+                "{translation} != {expected}"
+            );
+        }
+    }
+
+    /// Target at the origin of the world and one under a scaled and rotated parent
+    fn targets() -> Targets {
+        Targets {
+            parents: vec![
+                DMat4::IDENTITY,
+                DMat4::from_scale_rotation_translation(
+                    DVec3::new(2., 1., 0.5),
+                    DQuat::from_rotation_y(90_f64.to_radians()),
+                    DVec3::new(10., 0., 0.),
+                ),
+            ],
+            constraints: vec![
+                Constraints::default(),
+                Constraints {
+                    locked_translation: BVec3::new(false, true, false),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn translated(translation: DVec3) -> Transform {
+        Transform::from_scale_rotation_translation(DVec3::ONE, DQuat::IDENTITY, translation)
+    }
+
+    #[test]
+    fn world_transforms_go_through_each_target_parent() {
+        let mut targets = targets();
+        targets.set_local(vec![translated(DVec3::new(1., 2., 4.)); 2]);
+
+        assert_translations_eq(targets.local(), &[DVec3::new(1., 2., 4.); 2]);
+        // Scaled to (2, 2, 2), turned a quarter around Y, then moved along X
+        assert_translations_eq(
+            targets.world(),
+            &[DVec3::new(1., 2., 4.), DVec3::new(12., 2., -2.)],
+        );
+    }
+
+    #[test]
+    fn setting_local_transforms_keeps_parents_and_constraints() {
+        let mut targets = targets();
+        targets.set_local(vec![translated(DVec3::ZERO); 2]);
+        targets.set_local(vec![translated(DVec3::X); 2]);
+
+        assert_eq!(targets.parents(), self::targets().parents());
+        assert_eq!(
+            targets.constraints()[1].locked_translation,
+            BVec3::new(false, true, false)
+        );
+        assert_translations_eq(targets.world(), &[DVec3::X, DVec3::new(10., 0., -2.)]);
+    }
+}
@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Handles of a `Gizmo` rendered as a QtQuick3D geometry, depth tested against the scene.
+//!
+//! ```qml
+//! Model {
+//!     geometry: GizmoGeometry { gizmo: gizmo }
+//!     materials: DefaultMaterial {
+//!         vertexColorsEnabled: true
+//!         lighting: DefaultMaterial.NoLighting
+//!         cullMode: Material.NoCulling
+//!     }
+//! }
+//! ```
+//!
+//! The geometry is entirely built by `GizmoGeometryBase` from the data the `Gizmo` publishes on
+//! the GUI thread before each frame is synchronized, each handle being placed on the world plane
+//! it lies on. `GizmoOccludedModel` draws the parts hidden by the scene dimmed, comparing the handles
+//! with the depth texture of the view.
+
+#[cxx_qt::bridge]
+pub mod ffi {
+    unsafe extern "C++" {
+        include!("gizmo_geometry.h");
+
+        type GizmoGeometryBase;
+    }
+
+    unsafe extern "RustQt" {
+        #[qobject]
+        #[qml_element]
+        #[base = GizmoGeometryBase]
+        type GizmoGeometry = super::GizmoGeometryRust;
+    }
+}
+
+#[derive(Default)]
+pub struct GizmoGeometryRust;
@@ -0,0 +1,117 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Visuals shared by many gizmos, to theme them at once.
+//!
+//! ```qml
+//! GizmoStyle {
+//!     id: gizmoStyle
+//!     sColor: "#80ffffff"
+//!     xHoverColor: "yellow"
+//!     rotateGizmoSize: 100
+//! }
+//!
+//! Gizmo {
+//!     style: gizmoStyle
+//! }
+//! ```
+//!
+//! A gizmo with a style ignores its own `xColor`, `yColor`, `zColor`, `sColor`, `inactiveAlpha`,
+//! `highlightAlpha`, `strokeWidth` and `gizmoSize`.
+//!
+//! `translateGizmoSize`, `rotateGizmoSize` and `scaleGizmoSize` size a whole gizmo by the kind of
+//! handles it shows, not the handles themselves: `transform_gizmo` lays all the handles of a gizmo
+//! out from one size, so a gizmo mixing kinds of handles takes the largest size of the kinds it
+//! shows.
+
+use cxx_qt_lib::QColor;
+
+#[cxx_qt::bridge]
+pub mod ffi {
+    unsafe extern "C++" {
+        include!("gizmo_style.h");
+
+        type GizmoStyleBase;
+
+        include!("cxx-qt-lib/qcolor.h");
+        type QColor = cxx_qt_lib::QColor;
+    }
+
+    unsafe extern "RustQt" {
+        #[qobject]
+        #[qml_element]
+        #[base = GizmoStyleBase]
+        #[qproperty(QColor, xColor, rust_name = "x_color")]
+        #[qproperty(QColor, yColor, rust_name = "y_color")]
+        #[qproperty(QColor, zColor, rust_name = "z_color")]
+        #[qproperty(QColor, sColor, rust_name = "s_color")]
+        #[qproperty(QColor, xHoverColor, rust_name = "x_hover_color")]
+        #[qproperty(QColor, yHoverColor, rust_name = "y_hover_color")]
+        #[qproperty(QColor, zHoverColor, rust_name = "z_hover_color")]
+        #[qproperty(QColor, sHoverColor, rust_name = "s_hover_color")]
+        #[qproperty(f32, inactiveAlpha, rust_name = "inactive_alpha")]
+        #[qproperty(f32, highlightAlpha, rust_name = "highlight_alpha")]
+        #[qproperty(f32, strokeWidth, rust_name = "stroke_width")]
+        #[qproperty(f32, translateGizmoSize, rust_name = "translate_gizmo_size")]
+        #[qproperty(f32, rotateGizmoSize, rust_name = "rotate_gizmo_size")]
+        #[qproperty(f32, scaleGizmoSize, rust_name = "scale_gizmo_size")]
+        type GizmoStyle = super::GizmoStyleRust;
+    }
+
+    unsafe extern "C++" {
+        /// `style` as the `GizmoStyle` it was created as
+        fn gizmo_style_from_base(style: &GizmoStyleBase) -> &GizmoStyle;
+    }
+}
+
+/// Visuals shared by gizmos. Color alphas are multiplied by the inactive or highlight alpha.
+pub struct GizmoStyleRust {
+    x_color: QColor,
+    y_color: QColor,
+    z_color: QColor,
+    /// Color of the view handles: free translation, view rotation and uniform scaling
+    s_color: QColor,
+    /// Color of the hovered or dragged X handles, `x_color` when invalid
+    x_hover_color: QColor,
+    /// Color of the hovered or dragged Y handles, `y_color` when invalid
+    y_hover_color: QColor,
+    /// Color of the hovered or dragged Z handles, `z_color` when invalid
+    z_hover_color: QColor,
+    /// Color of the hovered or dragged view handles, `s_color` when invalid
+    s_hover_color: QColor,
+    /// Alpha of the handles that are neither hovered nor dragged
+    inactive_alpha: f32,
+    /// Alpha of the hovered or dragged handle
+    highlight_alpha: f32,
+    /// Width (thickness) of the gizmo strokes
+    stroke_width: f32,
+    /// Size in pixels of a gizmo showing translation handles only. All the handles of a gizmo
+    /// share one size, a gizmo mixing kinds of handles takes the largest size of the kinds it
+    /// shows.
+    translate_gizmo_size: f32,
+    /// Size in pixels of a gizmo showing rotation handles only
+    rotate_gizmo_size: f32,
+    /// Size in pixels of a gizmo showing scale handles only
+    scale_gizmo_size: f32,
+}
+
+impl Default for GizmoStyleRust {
+    fn default() -> Self {
+        Self {
+            x_color: QColor::from_rgb(255, 0, 125),
+            y_color: QColor::from_rgb(0, 255, 125),
+            z_color: QColor::from_rgb(0, 125, 255),
+            s_color: QColor::from_rgb(255, 255, 255),
+            x_hover_color: QColor::default(),
+            y_hover_color: QColor::default(),
+            z_hover_color: QColor::default(),
+            s_hover_color: QColor::default(),
+            inactive_alpha: 0.7,
+            highlight_alpha: 1.,
+            stroke_width: 4.,
+            translate_gizmo_size: 75.,
+            rotate_gizmo_size: 75.,
+            scale_gizmo_size: 75.,
+        }
+    }
+}
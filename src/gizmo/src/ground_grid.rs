@@ -0,0 +1,457 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Grid on the XZ plane drawn over a QtQuick3D view, placed below the view to appear behind the
+//! scene objects.
+//!
+//! ```qml
+//! GroundGrid {
+//!     anchors.fill: view
+//!     cameraPosition: camera.position
+//!     cameraRotation: camera.rotation.toVector4d()
+//!     cameraVerticalFoV: camera.fieldOfView
+//!     cameraNearPlane: camera.clipNear
+//!     cameraFarPlane: camera.clipFar
+//!     snapDistance: gizmo.snapDistance
+//! }
+//! ```
+//!
+//! Lines are spaced by powers of ten depending on the camera height, every tenth line being a
+//! major one. The minor lines fade out as the camera rises until they become the major lines of
+//! the next level.
+
+use core::pin::Pin;
+
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::{QColor, QVector3D, QVector4D};
+use ffi::{QQuickItemFlag, QQuickItemUpdatePaintNodeData, QSGNode};
+
+use crate::{
+    camera::Camera,
+    overlay::{self, OverlayMesh},
+};
+
+#[cxx_qt::bridge]
+pub mod ffi {
+    // Shared with the gizmo, whose scene graph rendering is reused
+    #[namespace = "rust::cxxqtlib1"]
+    unsafe extern "C++" {
+        include!("cxxqtlib1_qquickitem.h");
+        type QQuickItemFlag = crate::gizmo::ffi::QQuickItemFlag;
+        type QQuickItemUpdatePaintNodeData = crate::gizmo::ffi::QQuickItemUpdatePaintNodeData;
+    }
+
+    unsafe extern "C++" {
+        include!(<QtQuick/QSGNode>);
+        type QSGNode = crate::gizmo::ffi::QSGNode;
+    }
+
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qsizef.h");
+        type QSizeF = cxx_qt_lib::QSizeF;
+
+        include!("cxx-qt-lib/qvector3d.h");
+        type QVector3D = cxx_qt_lib::QVector3D;
+
+        include!("cxx-qt-lib/qvector4d.h");
+        type QVector4D = cxx_qt_lib::QVector4D;
+
+        include!("cxx-qt-lib/qcolor.h");
+        type QColor = cxx_qt_lib::QColor;
+
+        include!(<QtQuick/QQuickItem>);
+        type QQuickItem;
+    }
+
+    unsafe extern "RustQt" {
+        #[qobject]
+        #[qml_element]
+        #[base = QQuickItem]
+        #[qproperty(QVector3D, cameraPosition, rust_name = "camera_position")]
+        #[qproperty(QVector4D, cameraRotation, rust_name = "camera_rotation")]
+        #[qproperty(f32, cameraVerticalFoV, rust_name = "camera_vertical_fov")]
+        #[qproperty(f32, cameraNearPlane, rust_name = "camera_near_plane")]
+        #[qproperty(f32, cameraFarPlane, rust_name = "camera_far_plane")]
+        #[qproperty(QColor, lineColor, rust_name = "line_color")]
+        #[qproperty(QColor, xColor, rust_name = "x_color")]
+        #[qproperty(QColor, zColor, rust_name = "z_color")]
+        #[qproperty(f32, snapDistance, rust_name = "snap_distance")]
+        type GroundGrid = super::GroundGridRust;
+
+        #[inherit]
+        #[rust_name = "set_flag"]
+        fn setFlag(self: Pin<&mut GroundGrid>, flag: QQuickItemFlag, enabled: bool);
+
+        #[inherit]
+        fn update(self: Pin<&mut GroundGrid>);
+
+        #[inherit]
+        fn size(self: &GroundGrid) -> QSizeF;
+
+        #[cxx_override]
+        #[cxx_name = "updatePaintNode"]
+        unsafe fn update_paint_node(
+            self: Pin<&mut GroundGrid>,
+            old_node: *mut QSGNode,
+            update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+        ) -> *mut QSGNode;
+    }
+}
+
+/// Lines of a level drawn on each side of the camera
+const HALF_LINE_COUNT: i64 = 50;
+/// Lines are split in segments so their opacity follows the distance to the camera
+const SEGMENT_COUNT: usize = 16;
+/// Camera height under which the spacing stops decreasing
+const MIN_HEIGHT: f32 = 1e-3;
+const MINOR_LINE_WIDTH: f32 = 1.;
+const MAJOR_LINE_WIDTH: f32 = 1.5;
+const AXIS_LINE_WIDTH: f32 = 2.;
+
+pub struct GroundGridRust {
+    camera_position: QVector3D,
+    /// Rotation of the camera, as a quaternion (x, y, z, w)
+    camera_rotation: QVector4D,
+    /// Vertical field of view of the camera, in degrees
+    camera_vertical_fov: f32,
+    camera_near_plane: f32,
+    camera_far_plane: f32,
+    /// Color of the grid lines
+    line_color: QColor,
+    /// Color of the X axis
+    x_color: QColor,
+    /// Color of the Z axis
+    z_color: QColor,
+    /// When positive, lines are placed on multiples of this distance
+    snap_distance: f32,
+}
+
+impl Default for GroundGridRust {
+    fn default() -> Self {
+        Self {
+            camera_position: QVector3D::default(),
+            camera_rotation: QVector4D::new(0., 0., 0., 1.),
+            camera_vertical_fov: 60.,
+            camera_near_plane: 10.,
+            camera_far_plane: 10000.,
+            line_color: QColor::from_rgba(255, 255, 255, 96),
+            x_color: QColor::from_rgb(255, 0, 125),
+            z_color: QColor::from_rgb(0, 125, 255),
+            snap_distance: 0.,
+        }
+    }
+}
+
+impl GroundGridRust {
+    fn camera(&self) -> Camera {
+        Camera::new(
+            &self.camera_position,
+            &self.camera_rotation,
+            self.camera_vertical_fov,
+            self.camera_near_plane,
+            self.camera_far_plane,
+        )
+    }
+}
+
+impl cxx_qt::Initialize for ffi::GroundGrid {
+    fn initialize(mut self: Pin<&mut Self>) {
+        self.as_mut()
+            .set_flag(QQuickItemFlag::ItemHasContents, true);
+
+        self.as_mut()
+            .on_camera_position_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_rotation_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_vertical_fov_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_near_plane_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_far_plane_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_line_color_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_x_color_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_z_color_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_snap_distance_changed(|qobject| qobject.update())
+            .release();
+    }
+}
+
+impl ffi::GroundGrid {
+    unsafe fn update_paint_node(
+        self: Pin<&mut Self>,
+        old_node: *mut QSGNode,
+        _update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+    ) -> *mut QSGNode {
+        let size = self.size();
+        let size = glam::Vec2::new(size.width() as f32, size.height() as f32);
+        let this = self.rust();
+        let camera = this.camera();
+
+        let mut mesh = OverlayMesh::default();
+        if size.min_element() > 0. {
+            let grid = Grid {
+                camera: &camera,
+                size,
+                center: glam::Vec2::new(camera.position.x, camera.position.z),
+            };
+            let (minor, opacity) = minor_spacing(camera.position.y.abs(), this.snap_distance);
+            let major = minor * 10.;
+            let line_color = overlay::rgba(&this.line_color);
+            let [red, green, blue, alpha] = line_color;
+
+            grid.lines(
+                &mut mesh,
+                minor,
+                [red, green, blue, alpha * opacity],
+                MINOR_LINE_WIDTH,
+                |index| index % 10 != 0,
+            );
+            grid.lines(&mut mesh, major, line_color, MAJOR_LINE_WIDTH, |index| {
+                index != 0
+            });
+
+            let extent = major * HALF_LINE_COUNT as f32;
+            let x_axis = (
+                glam::Vec2::new(grid.center.x - extent, 0.),
+                glam::Vec2::new(grid.center.x + extent, 0.),
+            );
+            let z_axis = (
+                glam::Vec2::new(0., grid.center.y - extent),
+                glam::Vec2::new(0., grid.center.y + extent),
+            );
+            grid.line(
+                &mut mesh,
+                x_axis,
+                extent,
+                overlay::rgba(&this.x_color),
+                AXIS_LINE_WIDTH,
+            );
+            grid.line(
+                &mut mesh,
+                z_axis,
+                extent,
+                overlay::rgba(&this.z_color),
+                AXIS_LINE_WIDTH,
+            );
+        }
+
+        unsafe {
+            crate::gizmo::ffi::gizmo_update_paint_node(
+                old_node,
+                &mesh.vertices,
+                &mesh.rgba8_colors(),
+                &mesh.indices,
+            )
+        }
+    }
+}
+
+/// Spacing of the minor lines seen from `height`, and their opacity. Lines are placed on multiples
+/// of `snap_distance` when it is positive.
+fn minor_spacing(height: f32, snap_distance: f32) -> (f32, f32) {
+    // Between 10 and 100 minor lines along the camera height
+    let level = (height.max(MIN_HEIGHT) / 10.).log10();
+    let spacing = 10f32.powf(level.floor());
+    let opacity = 1. - (level - level.floor());
+
+    if snap_distance.is_finite() && snap_distance > 0. {
+        let power = (spacing / snap_distance).log10().round();
+        (snap_distance * 10f32.powf(power), opacity)
+    } else {
+        (spacing, opacity)
+    }
+}
+
+/// Ground seen by the camera, positions on the ground being (x, z) world coordinates
+struct Grid<'a> {
+    camera: &'a Camera,
+    size: glam::Vec2,
+    /// Point of the ground below the camera
+    center: glam::Vec2,
+}
+
+impl Grid<'_> {
+    /// Lines parallel to the X and Z axes every `spacing` around the camera. Lines are skipped
+    /// when `filter` refuses their index, their position divided by `spacing`.
+    fn lines(
+        &self,
+        mesh: &mut OverlayMesh,
+        spacing: f32,
+        color: [f32; 4],
+        width: f32,
+        filter: impl Fn(i64) -> bool,
+    ) {
+        let extent = spacing * HALF_LINE_COUNT as f32;
+        let first_x = (self.center.x / spacing).round() as i64 - HALF_LINE_COUNT;
+        let first_z = (self.center.y / spacing).round() as i64 - HALF_LINE_COUNT;
+
+        for i in 0..=2 * HALF_LINE_COUNT {
+            if filter(first_x + i) {
+                let x = (first_x + i) as f32 * spacing;
+                let line = (
+                    glam::Vec2::new(x, self.center.y - extent),
+                    glam::Vec2::new(x, self.center.y + extent),
+                );
+                self.line(mesh, line, extent, color, width);
+            }
+            if filter(first_z + i) {
+                let z = (first_z + i) as f32 * spacing;
+                let line = (
+                    glam::Vec2::new(self.center.x - extent, z),
+                    glam::Vec2::new(self.center.x + extent, z),
+                );
+                self.line(mesh, line, extent, color, width);
+            }
+        }
+    }
+
+    /// Line on the ground, transparent at `extent` from the camera
+    fn line(
+        &self,
+        mesh: &mut OverlayMesh,
+        (start, end): (glam::Vec2, glam::Vec2),
+        extent: f32,
+        color: [f32; 4],
+        width: f32,
+    ) {
+        let faded = |point: glam::Vec2| {
+            let [red, green, blue, alpha] = color;
+            let opacity = (1. - point.distance(self.center) / extent).max(0.);
+            [red, green, blue, alpha * opacity * opacity]
+        };
+
+        for segment in 0..SEGMENT_COUNT {
+            let from = start.lerp(end, segment as f32 / SEGMENT_COUNT as f32);
+            let to = start.lerp(end, (segment + 1) as f32 / SEGMENT_COUNT as f32);
+            let colors = (faded(from), faded(to));
+            if colors.0[3] <= 0. && colors.1[3] <= 0. {
+                continue;
+            }
+
+            overlay::world_line(
+                mesh,
+                self.camera,
+                self.size,
+                (
+                    glam::Vec3::new(from.x, 0., from.y),
+                    glam::Vec3::new(to.x, 0., to.y),
+                ),
+                width,
+                colors,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_spacing_eq((spacing, opacity): (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (spacing - expected.0).abs() <= EPSILON * expected.0
+                && (opacity - expected.1).abs() <= EPSILON,
+            "{:?} != {expected:?}",
+            (spacing, opacity)
+        );
+    }
+
+    /// Camera at `height` above the origin, looking down
+    fn camera(height: f32) -> Camera {
+        Camera {
+            origin: glam::DVec3::ZERO,
+            position: glam::Vec3::new(0., height, 0.),
+            rotation: glam::Quat::from_rotation_x(-90f32.to_radians()),
+            vertical_fov: 60.,
+            near_plane: 0.1,
+            far_plane: 1000.,
+        }
+    }
+
+    #[test]
+    fn spacing_steps_by_powers_of_ten_with_the_height() {
+        assert_spacing_eq(minor_spacing(10., 0.), (1., 1.));
+        assert_spacing_eq(minor_spacing(50., 0.), (1., 1. - 5f32.log10()));
+        assert_spacing_eq(minor_spacing(100., 0.), (10., 1.));
+        assert_spacing_eq(minor_spacing(1., 0.), (0.1, 1.));
+    }
+
+    #[test]
+    fn minor_lines_fade_out_before_the_next_level() {
+        let (_, opacity) = minor_spacing(99.9, 0.);
+        assert!(opacity < 0.01, "{opacity}");
+    }
+
+    #[test]
+    fn spacing_stops_decreasing_at_the_minimum_height() {
+        assert_eq!(minor_spacing(0., 0.), minor_spacing(MIN_HEIGHT, 0.));
+        assert_eq!(minor_spacing(-1., 0.), minor_spacing(MIN_HEIGHT, 0.));
+    }
+
+    #[test]
+    fn snapped_spacing_is_the_snap_distance_times_a_power_of_ten() {
+        assert_spacing_eq(minor_spacing(10., 0.25), (2.5, 1.));
+        assert_spacing_eq(minor_spacing(1., 0.25), (0.25, 1.));
+        assert_spacing_eq(minor_spacing(100., 0.25), (25., 1.));
+    }
+
+    #[test]
+    fn invalid_snap_distances_are_ignored() {
+        for snap_distance in [-1., f32::NAN, f32::INFINITY] {
+            assert_eq!(minor_spacing(10., snap_distance), (1., 1.));
+        }
+    }
+
+    #[test]
+    fn lines_fade_out_at_the_extent() {
+        let camera = camera(10.);
+        let grid = Grid {
+            camera: &camera,
+            size: glam::Vec2::new(800., 600.),
+            center: glam::Vec2::ZERO,
+        };
+
+        let mut through_center = OverlayMesh::default();
+        let line = (glam::Vec2::new(-10., 0.), glam::Vec2::new(10., 0.));
+        grid.line(&mut through_center, line, 20., [1.; 4], 1.);
+        assert!(!through_center.vertices.is_empty());
+
+        let mut beyond_extent = OverlayMesh::default();
+        let line = (glam::Vec2::new(-10., 20.), glam::Vec2::new(10., 20.));
+        grid.line(&mut beyond_extent, line, 20., [1.; 4], 1.);
+        assert!(beyond_extent.vertices.is_empty());
+    }
+
+    #[test]
+    fn filtered_lines_are_skipped() {
+        let camera = camera(10.);
+        let grid = Grid {
+            camera: &camera,
+            size: glam::Vec2::new(800., 600.),
+            center: glam::Vec2::ZERO,
+        };
+
+        let mut all = OverlayMesh::default();
+        grid.lines(&mut all, 1., [1.; 4], 1., |_| true);
+        let mut none = OverlayMesh::default();
+        grid.lines(&mut none, 1., [1.; 4], 1., |_| false);
+
+        assert!(!all.vertices.is_empty());
+        assert!(none.vertices.is_empty());
+    }
+}
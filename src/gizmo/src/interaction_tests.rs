@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Input tests of the gizmo on the offscreen platform.
+//!
+//! Qt allows a single application per process, so each test runs again alone in a child
+//! process, where it creates the application and plays its scenario from `gizmo_tests.cpp`.
+
+#[cxx_qt::bridge]
+pub mod ffi {
+    unsafe extern "C++" {
+        include!("gizmo_tests.h");
+
+        fn touch_drag_moves_target() -> String;
+        fn touch_outside_handles_is_ignored() -> String;
+        fn touch_second_point_is_ignored() -> String;
+        fn tablet_drag_moves_target() -> String;
+        fn tablet_outside_handles_is_ignored() -> String;
+        fn scaled_window_picks_in_logical_pixels(scale_factor: f64) -> String;
+        fn geospatial_drag_keeps_double_precision() -> String;
+    }
+}
+
+use std::process::Command;
+
+use cxx_qt_lib::QGuiApplication;
+
+/// Set in the child process running a single scenario
+const CHILD_ENV: &str = "GIZMO_INTERACTION_TEST";
+
+/// Play `scenario` in a child process with `envs` set, failing with the error it returns
+fn run_in_application(test: &str, envs: &[(&str, &str)], scenario: fn() -> String) {
+    if std::env::var_os(CHILD_ENV).is_some() {
+        let _app = QGuiApplication::new();
+        let error = scenario();
+        assert!(error.is_empty(), "{error}");
+        return;
+    }
+
+    // Test names don't include the crate name
+    let module = module_path!()
+        .split_once("::")
+        .map_or("", |(_, module)| module);
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "--nocapture", "--test-threads=1"])
+        .arg(format!("{module}::{test}"))
+        .env(CHILD_ENV, "1")
+        .env("QT_QPA_PLATFORM", "offscreen")
+        .envs(envs.iter().copied())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn touch_drag_moves_target() {
+    run_in_application("touch_drag_moves_target", &[], ffi::touch_drag_moves_target);
+}
+
+#[test]
+fn touch_outside_handles_is_ignored() {
+    run_in_application(
+        "touch_outside_handles_is_ignored",
+        &[],
+        ffi::touch_outside_handles_is_ignored,
+    );
+}
+
+#[test]
+fn touch_second_point_is_ignored() {
+    run_in_application(
+        "touch_second_point_is_ignored",
+        &[],
+        ffi::touch_second_point_is_ignored,
+    );
+}
+
+#[test]
+fn tablet_drag_moves_target() {
+    run_in_application(
+        "tablet_drag_moves_target",
+        &[],
+        ffi::tablet_drag_moves_target,
+    );
+}
+
+#[test]
+fn tablet_outside_handles_is_ignored() {
+    run_in_application(
+        "tablet_outside_handles_is_ignored",
+        &[],
+        ffi::tablet_outside_handles_is_ignored,
+    );
+}
+
+#[test]
+fn picking_at_scale_factor_1() {
+    run_in_application(
+        "picking_at_scale_factor_1",
+        &[("QT_SCALE_FACTOR", "1")],
+        || ffi::scaled_window_picks_in_logical_pixels(1.),
+    );
+}
+
+#[test]
+fn picking_at_scale_factor_1_5() {
+    run_in_application(
+        "picking_at_scale_factor_1_5",
+        &[("QT_SCALE_FACTOR", "1.5")],
+        || ffi::scaled_window_picks_in_logical_pixels(1.5),
+    );
+}
+
+#[test]
+fn picking_at_scale_factor_2() {
+    run_in_application(
+        "picking_at_scale_factor_2",
+        &[("QT_SCALE_FACTOR", "2")],
+        || ffi::scaled_window_picks_in_logical_pixels(2.),
+    );
+}
+
+#[test]
+fn geospatial_drag_keeps_double_precision() {
+    run_in_application(
+        "geospatial_drag_keeps_double_precision",
+        &[],
+        ffi::geospatial_drag_keeps_double_precision,
+    );
+}
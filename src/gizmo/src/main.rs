@@ -1,7 +1,17 @@
 // SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
 // SPDX-License-Identifier: MIT
 
+mod camera;
 pub mod gizmo;
+pub mod gizmo_geometry;
+pub mod gizmo_style;
+pub mod ground_grid;
+#[cfg(all(test, feature = "interaction-tests"))]
+mod interaction_tests;
+pub mod marquee_selector;
+pub mod measure_tool;
+mod overlay;
+pub mod view_cube;
 use cxx_qt_lib::{QGuiApplication, QQmlApplicationEngine, QUrl};
 
 fn main() {
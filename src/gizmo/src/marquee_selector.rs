@@ -0,0 +1,512 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Rectangle dragged over a QtQuick3D view to select the nodes it contains.
+//!
+//! ```qml
+//! MarqueeSelector {
+//!     anchors.fill: view
+//!     cameraPosition: camera.position
+//!     cameraRotation: camera.rotation.toVector4d()
+//!     cameraVerticalFoV: camera.fieldOfView
+//!     cameraNearPlane: camera.clipNear
+//!     cameraFarPlane: camera.clipFar
+//!     nodes: [cube1, cube2]
+//!     selection: Array.from(view.pickedModels)
+//!     onSelected: (nodes) => view.pickedModels = nodes
+//! }
+//! ```
+//!
+//! Like in Blender, holding Shift adds to the selection, Ctrl subtracts from it and both
+//! intersect with it, whatever the `mode`.
+
+use core::pin::Pin;
+
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::{QColor, QPointF, QVariant, QVector3D, QVector4D};
+use ffi::{
+    MarqueeContainment, MarqueeMode, QQuickItemFlag, QQuickItemUpdatePaintNodeData, QSGNode,
+};
+
+use crate::{
+    camera::Camera,
+    gizmo::ffi::{
+        extract_snap_point_count_from_qvariant, extract_snap_points_from_qvariant,
+        extract_target_count_from_qvariant, qvariant_list_at,
+    },
+    overlay::{self, OverlayMesh},
+};
+
+#[cxx_qt::bridge]
+pub mod ffi {
+    // Shared with the gizmo, whose scene graph rendering is reused
+    #[namespace = "rust::cxxqtlib1"]
+    unsafe extern "C++" {
+        include!("cxxqtlib1_qquickitem.h");
+        type QQuickItemFlag = crate::gizmo::ffi::QQuickItemFlag;
+        type QQuickItemUpdatePaintNodeData = crate::gizmo::ffi::QQuickItemUpdatePaintNodeData;
+    }
+
+    unsafe extern "C++" {
+        include!(<QtQuick/QSGNode>);
+        type QSGNode = crate::gizmo::ffi::QSGNode;
+    }
+
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qpointf.h");
+        type QPointF = cxx_qt_lib::QPointF;
+
+        include!("cxx-qt-lib/qsizef.h");
+        type QSizeF = cxx_qt_lib::QSizeF;
+
+        include!("cxx-qt-lib/qvector3d.h");
+        type QVector3D = cxx_qt_lib::QVector3D;
+
+        include!("cxx-qt-lib/qvector4d.h");
+        type QVector4D = cxx_qt_lib::QVector4D;
+
+        include!("cxx-qt-lib/qcolor.h");
+        type QColor = cxx_qt_lib::QColor;
+
+        include!("cxx-qt-lib/qvariant.h");
+        type QVariant = cxx_qt_lib::QVariant;
+    }
+
+    /// How the nodes inside the rectangle change the selection.
+    #[qenum(MarqueeSelector)]
+    enum MarqueeMode {
+        /// The nodes inside the rectangle replace the selection
+        Replace,
+        /// The nodes inside the rectangle are added to the selection
+        Add,
+        /// The nodes inside the rectangle are removed from the selection
+        Subtract,
+        /// Only the selected nodes inside the rectangle stay selected
+        Intersect,
+    }
+
+    /// What must be inside the rectangle for a node to be selected.
+    #[qenum(MarqueeSelector)]
+    enum MarqueeContainment {
+        /// The origin of the node
+        Origin,
+        /// The whole bounding box of the node, or its origin when it has no bounds
+        Bounds,
+    }
+
+    unsafe extern "RustQt" {
+        #[qobject]
+        #[qml_element]
+        #[base = MarqueeInteractionItem]
+        #[qproperty(QVector3D, cameraPosition, rust_name = "camera_position")]
+        #[qproperty(QVector4D, cameraRotation, rust_name = "camera_rotation")]
+        #[qproperty(f32, cameraVerticalFoV, rust_name = "camera_vertical_fov")]
+        #[qproperty(f32, cameraNearPlane, rust_name = "camera_near_plane")]
+        #[qproperty(f32, cameraFarPlane, rust_name = "camera_far_plane")]
+        #[qproperty(QVariant, nodes)]
+        #[qproperty(QVariant, selection)]
+        #[qproperty(MarqueeMode, mode)]
+        #[qproperty(MarqueeContainment, containment)]
+        #[qproperty(QColor, fillColor, rust_name = "fill_color")]
+        #[qproperty(QColor, borderColor, rust_name = "border_color")]
+        type MarqueeSelector = super::MarqueeSelectorRust;
+
+        #[inherit]
+        #[rust_name = "set_flag"]
+        fn setFlag(self: Pin<&mut MarqueeSelector>, flag: QQuickItemFlag, enabled: bool);
+
+        #[inherit]
+        fn update(self: Pin<&mut MarqueeSelector>);
+
+        #[inherit]
+        fn size(self: &MarqueeSelector) -> QSizeF;
+
+        #[cxx_override]
+        #[cxx_name = "updateMarquee"]
+        fn update_marquee(self: Pin<&mut MarqueeSelector>, start: QPointF, end: QPointF);
+
+        #[cxx_override]
+        #[cxx_name = "finishMarquee"]
+        fn finish_marquee(
+            self: Pin<&mut MarqueeSelector>,
+            start: QPointF,
+            end: QPointF,
+            modifiers: i32,
+        );
+
+        #[cxx_override]
+        #[cxx_name = "cancelMarquee"]
+        fn cancel_marquee(self: Pin<&mut MarqueeSelector>);
+
+        #[cxx_override]
+        #[cxx_name = "updatePaintNode"]
+        unsafe fn update_paint_node(
+            self: Pin<&mut MarqueeSelector>,
+            old_node: *mut QSGNode,
+            update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+        ) -> *mut QSGNode;
+
+        /// Emitted when the rectangle is released, with the new selection
+        #[qsignal]
+        fn selected(self: Pin<&mut MarqueeSelector>, nodes: QVariant);
+    }
+
+    unsafe extern "C++" {
+        include!("marquee_selector.h");
+        type MarqueeInteractionItem;
+
+        fn qvariant_list_select(list: &QVariant, indices: &[usize]) -> QVariant;
+
+        fn qvariant_list_contains(list: &QVariant, item: &QVariant) -> bool;
+
+        fn qvariant_list_concat(first: &QVariant, second: &QVariant) -> QVariant;
+    }
+}
+
+impl Default for MarqueeMode {
+    fn default() -> Self {
+        Self::Replace
+    }
+}
+
+impl Default for MarqueeContainment {
+    fn default() -> Self {
+        Self::Origin
+    }
+}
+
+// Values of `Qt::KeyboardModifier`
+const QT_SHIFT_MODIFIER: i32 = 0x0200_0000;
+const QT_CONTROL_MODIFIER: i32 = 0x0400_0000;
+
+const BORDER_WIDTH: f32 = 1.;
+
+pub struct MarqueeSelectorRust {
+    camera_position: QVector3D,
+    /// Rotation of the camera, as a quaternion (x, y, z, w)
+    camera_rotation: QVector4D,
+    /// Vertical field of view of the camera, in degrees
+    camera_vertical_fov: f32,
+    camera_near_plane: f32,
+    camera_far_plane: f32,
+    /// Nodes that can be selected, QtQuick3D nodes or positions
+    nodes: QVariant,
+    /// Current selection, changed according to the `mode`
+    selection: QVariant,
+    mode: MarqueeMode,
+    containment: MarqueeContainment,
+    fill_color: QColor,
+    border_color: QColor,
+
+    /// Corners of the rectangle being dragged
+    rectangle: Option<(glam::Vec2, glam::Vec2)>,
+}
+
+impl Default for MarqueeSelectorRust {
+    fn default() -> Self {
+        Self {
+            camera_position: QVector3D::default(),
+            camera_rotation: QVector4D::new(0., 0., 0., 1.),
+            camera_vertical_fov: 60.,
+            camera_near_plane: 10.,
+            camera_far_plane: 10000.,
+            nodes: QVariant::default(),
+            selection: QVariant::default(),
+            mode: MarqueeMode::default(),
+            containment: MarqueeContainment::default(),
+            fill_color: QColor::from_rgba(0, 125, 255, 48),
+            border_color: QColor::from_rgb(0, 125, 255),
+            rectangle: None,
+        }
+    }
+}
+
+impl MarqueeSelectorRust {
+    fn camera(&self) -> Camera {
+        Camera::new(
+            &self.camera_position,
+            &self.camera_rotation,
+            self.camera_vertical_fov,
+            self.camera_near_plane,
+            self.camera_far_plane,
+        )
+    }
+
+    /// Indices of the `nodes` inside the rectangle from `min` to `max`, seen in an item of `size`
+    fn nodes_inside(&self, min: glam::Vec2, max: glam::Vec2, size: glam::Vec2) -> Vec<usize> {
+        let point_count = extract_snap_point_count_from_qvariant(&self.nodes);
+        let mut points = vec![[0.; 3]; point_count];
+        let mut sources = vec![0; point_count];
+        // The marquee camera works in the QtQuick3D scene
+        extract_snap_points_from_qvariant(&self.nodes, &[0.; 3], &mut points, &mut sources);
+        let node_points = group_by_node(
+            points
+                .into_iter()
+                .map(|point| glam::DVec3::from_array(point).as_vec3())
+                .zip(sources),
+            extract_target_count_from_qvariant(&self.nodes),
+        );
+
+        let camera = self.camera();
+        select_inside(&node_points, self.containment, |point| {
+            camera
+                .world_to_screen(point, size.x, size.y)
+                .is_some_and(|point| point.cmpge(min).all() && point.cmple(max).all())
+        })
+    }
+
+    /// Selection once the `inside` nodes are combined to the current one with `mode`
+    fn combine(&self, inside: &QVariant, mode: MarqueeMode) -> QVariant {
+        let selection_inside: Vec<bool> = (0..extract_target_count_from_qvariant(&self.selection))
+            .map(|index| {
+                ffi::qvariant_list_contains(inside, &qvariant_list_at(&self.selection, index))
+            })
+            .collect();
+        let inside_selected: Vec<bool> = (0..extract_target_count_from_qvariant(inside))
+            .map(|index| {
+                ffi::qvariant_list_contains(&self.selection, &qvariant_list_at(inside, index))
+            })
+            .collect();
+        let (kept, added) = combine_indices(mode, &selection_inside, &inside_selected);
+
+        ffi::qvariant_list_concat(
+            &ffi::qvariant_list_select(&self.selection, &kept),
+            &ffi::qvariant_list_select(inside, &added),
+        )
+    }
+}
+
+/// Points of each of `node_count` nodes, from points paired with the index of their node
+///
+/// Snap points are the origin of a node followed by the corners of its bounds, so the origin of a
+/// node comes first in its points.
+fn group_by_node(
+    points: impl Iterator<Item = (glam::Vec3, usize)>,
+    node_count: usize,
+) -> Vec<Vec<glam::Vec3>> {
+    let mut node_points = vec![Vec::new(); node_count];
+    for (point, node) in points {
+        if let Some(node_points) = node_points.get_mut(node) {
+            node_points.push(point);
+        }
+    }
+    node_points
+}
+
+/// Indices of the nodes, given by their `node_points`, whose tested points are all `inside`
+fn select_inside(
+    node_points: &[Vec<glam::Vec3>],
+    containment: MarqueeContainment,
+    inside: impl Fn(glam::Vec3) -> bool,
+) -> Vec<usize> {
+    node_points
+        .iter()
+        .enumerate()
+        .filter(|(_, points)| {
+            let tested = match (containment, points.split_first()) {
+                (_, None) => return false,
+                (MarqueeContainment::Bounds, Some((_, corners))) if !corners.is_empty() => corners,
+                (_, Some((origin, _))) => core::slice::from_ref(origin),
+            };
+            tested.iter().all(|&point| inside(point))
+        })
+        .map(|(node, _)| node)
+        .collect()
+}
+
+/// Indices of the selection kept and of the nodes inside the rectangle added when combining them
+/// with `mode`
+///
+/// `selection_inside` tells for each selected node whether it is inside the rectangle, and
+/// `inside_selected` for each node inside the rectangle whether it is selected.
+fn combine_indices(
+    mode: MarqueeMode,
+    selection_inside: &[bool],
+    inside_selected: &[bool],
+) -> (Vec<usize>, Vec<usize>) {
+    let kept = (0..selection_inside.len())
+        .filter(|&index| match mode {
+            MarqueeMode::Add => true,
+            MarqueeMode::Subtract => !selection_inside[index],
+            MarqueeMode::Intersect => selection_inside[index],
+            _ => false,
+        })
+        .collect();
+    let added = (0..inside_selected.len())
+        .filter(|&index| match mode {
+            MarqueeMode::Replace => true,
+            MarqueeMode::Add => !inside_selected[index],
+            _ => false,
+        })
+        .collect();
+    (kept, added)
+}
+
+impl cxx_qt::Initialize for ffi::MarqueeSelector {
+    fn initialize(mut self: Pin<&mut Self>) {
+        self.as_mut()
+            .set_flag(QQuickItemFlag::ItemHasContents, true);
+
+        self.as_mut()
+            .on_fill_color_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_border_color_changed(|qobject| qobject.update())
+            .release();
+    }
+}
+
+impl ffi::MarqueeSelector {
+    fn set_rectangle(mut self: Pin<&mut Self>, rectangle: Option<(glam::Vec2, glam::Vec2)>) {
+        self.as_mut().rust_mut().rectangle = rectangle;
+        self.update();
+    }
+
+    fn update_marquee(self: Pin<&mut Self>, start: QPointF, end: QPointF) {
+        self.set_rectangle(Some(corners(&start, &end)));
+    }
+
+    fn cancel_marquee(self: Pin<&mut Self>) {
+        self.set_rectangle(None);
+    }
+
+    fn finish_marquee(mut self: Pin<&mut Self>, start: QPointF, end: QPointF, modifiers: i32) {
+        self.as_mut().set_rectangle(None);
+
+        let mode = match (
+            modifiers & QT_SHIFT_MODIFIER != 0,
+            modifiers & QT_CONTROL_MODIFIER != 0,
+        ) {
+            (true, true) => MarqueeMode::Intersect,
+            (true, false) => MarqueeMode::Add,
+            (false, true) => MarqueeMode::Subtract,
+            (false, false) => self.rust().mode,
+        };
+        let size = self.size();
+        let size = glam::Vec2::new(size.width() as f32, size.height() as f32);
+        let (min, max) = corners(&start, &end);
+
+        let selection = {
+            let this = self.rust();
+            let inside = this.nodes_inside(min, max, size);
+            this.combine(&ffi::qvariant_list_select(&this.nodes, &inside), mode)
+        };
+        self.selected(selection);
+    }
+
+    unsafe fn update_paint_node(
+        self: Pin<&mut Self>,
+        old_node: *mut QSGNode,
+        _update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+    ) -> *mut QSGNode {
+        let this = self.rust();
+
+        let mut mesh = OverlayMesh::default();
+        if let Some((min, max)) = this.rectangle {
+            let corners = [
+                min,
+                glam::Vec2::new(max.x, min.y),
+                max,
+                glam::Vec2::new(min.x, max.y),
+            ];
+            mesh.quad(corners, [overlay::rgba(&this.fill_color); 4]);
+
+            let border = overlay::rgba(&this.border_color);
+            for i in 0..4 {
+                mesh.line(
+                    corners[i],
+                    corners[(i + 1) % 4],
+                    BORDER_WIDTH,
+                    border,
+                    border,
+                );
+            }
+        }
+
+        unsafe {
+            crate::gizmo::ffi::gizmo_update_paint_node(
+                old_node,
+                &mesh.vertices,
+                &mesh.rgba8_colors(),
+                &mesh.indices,
+            )
+        }
+    }
+}
+
+/// Minimum and maximum corners of the rectangle from `start` to `end`
+fn corners(start: &QPointF, end: &QPointF) -> (glam::Vec2, glam::Vec2) {
+    let start = glam::Vec2::new(start.x() as f32, start.y() as f32);
+    let end = glam::Vec2::new(end.x() as f32, end.y() as f32);
+    (start.min(end), start.max(end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether selected nodes 0 to 3 are inside, and whether inside nodes 0 to 2 are selected
+    const SELECTION_INSIDE: [bool; 4] = [true, false, true, false];
+    const INSIDE_SELECTED: [bool; 3] = [true, false, true];
+
+    fn combined(mode: MarqueeMode) -> (Vec<usize>, Vec<usize>) {
+        combine_indices(mode, &SELECTION_INSIDE, &INSIDE_SELECTED)
+    }
+
+    #[test]
+    fn replace_selects_only_nodes_inside() {
+        assert_eq!(combined(MarqueeMode::Replace), (vec![], vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn add_keeps_selection_and_adds_unselected_nodes_inside() {
+        assert_eq!(combined(MarqueeMode::Add), (vec![0, 1, 2, 3], vec![1]));
+    }
+
+    #[test]
+    fn subtract_removes_selected_nodes_inside() {
+        assert_eq!(combined(MarqueeMode::Subtract), (vec![1, 3], vec![]));
+    }
+
+    #[test]
+    fn intersect_keeps_selected_nodes_inside() {
+        assert_eq!(combined(MarqueeMode::Intersect), (vec![0, 2], vec![]));
+    }
+
+    #[test]
+    fn points_are_grouped_by_node_in_order() {
+        let points = [
+            (glam::Vec3::X, 1),
+            (glam::Vec3::Y, 0),
+            (glam::Vec3::Z, 1),
+            (glam::Vec3::ONE, 5),
+        ];
+        assert_eq!(
+            group_by_node(points.into_iter(), 3),
+            vec![
+                vec![glam::Vec3::Y],
+                vec![glam::Vec3::X, glam::Vec3::Z],
+                vec![]
+            ]
+        );
+    }
+
+    #[test]
+    fn bounds_containment_tests_corners_or_origin() {
+        let node_points = vec![
+            vec![glam::Vec3::ZERO, glam::Vec3::X, glam::Vec3::NEG_X],
+            vec![glam::Vec3::ZERO, glam::Vec3::X, glam::Vec3::splat(2.0)],
+            vec![glam::Vec3::ZERO],
+            vec![],
+        ];
+        let inside = |point: glam::Vec3| point.length() <= 1.0;
+        assert_eq!(
+            select_inside(&node_points, MarqueeContainment::Bounds, inside),
+            vec![0, 2]
+        );
+        assert_eq!(
+            select_inside(&node_points, MarqueeContainment::Origin, inside),
+            vec![0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,506 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Rulers placed by clicking in a QtQuick3D view, measuring the distance along the points, the
+//! angle at the last point and the area of the polygon they form.
+//!
+//! ```qml
+//! MeasureTool {
+//!     anchors.fill: view
+//!     view: view
+//!     cameraPosition: camera.position
+//!     cameraRotation: camera.rotation.toVector4d()
+//!     cameraVerticalFoV: camera.fieldOfView
+//!     cameraNearPlane: camera.clipNear
+//!     cameraFarPlane: camera.clipFar
+//!     unit: "cm"
+//! }
+//! ```
+//!
+//! Points are placed on the objects of `view` under the cursor, or on the ground plane.
+
+use core::pin::Pin;
+
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::{QColor, QPointF, QString, QVector3D, QVector4D};
+use ffi::{QQuickItemFlag, QQuickItemUpdatePaintNodeData, QSGNode};
+
+use crate::{
+    camera::Camera,
+    overlay::{self, OverlayMesh},
+};
+
+#[cxx_qt::bridge]
+pub mod ffi {
+    // Shared with the gizmo, whose scene graph rendering is reused
+    #[namespace = "rust::cxxqtlib1"]
+    unsafe extern "C++" {
+        include!("cxxqtlib1_qquickitem.h");
+        type QQuickItemFlag = crate::gizmo::ffi::QQuickItemFlag;
+        type QQuickItemUpdatePaintNodeData = crate::gizmo::ffi::QQuickItemUpdatePaintNodeData;
+    }
+
+    unsafe extern "C++" {
+        include!(<QtQuick/QSGNode>);
+        type QSGNode = crate::gizmo::ffi::QSGNode;
+    }
+
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qpointf.h");
+        type QPointF = cxx_qt_lib::QPointF;
+
+        include!("cxx-qt-lib/qsizef.h");
+        type QSizeF = cxx_qt_lib::QSizeF;
+
+        include!("cxx-qt-lib/qvector3d.h");
+        type QVector3D = cxx_qt_lib::QVector3D;
+
+        include!("cxx-qt-lib/qvector4d.h");
+        type QVector4D = cxx_qt_lib::QVector4D;
+
+        include!("cxx-qt-lib/qcolor.h");
+        type QColor = cxx_qt_lib::QColor;
+
+        include!("cxx-qt-lib/qstring.h");
+        type QString = cxx_qt_lib::QString;
+    }
+
+    unsafe extern "C++" {
+        include!("measure_tool.h");
+        type MeasureInteractionItem;
+    }
+
+    unsafe extern "RustQt" {
+        #[qobject]
+        #[qml_element]
+        #[base = MeasureInteractionItem]
+        #[qproperty(QVector3D, cameraPosition, rust_name = "camera_position")]
+        #[qproperty(QVector4D, cameraRotation, rust_name = "camera_rotation")]
+        #[qproperty(f32, cameraVerticalFoV, rust_name = "camera_vertical_fov")]
+        #[qproperty(f32, cameraNearPlane, rust_name = "camera_near_plane")]
+        #[qproperty(f32, cameraFarPlane, rust_name = "camera_far_plane")]
+        #[qproperty(QColor, lineColor, rust_name = "line_color")]
+        #[qproperty(QString, unit)]
+        #[qproperty(bool, closed)]
+        #[qproperty(i32, pointCount, rust_name = "point_count", READ, NOTIFY)]
+        #[qproperty(f32, distance, READ, NOTIFY)]
+        #[qproperty(f32, angle, READ, NOTIFY)]
+        #[qproperty(f32, area, READ, NOTIFY)]
+        type MeasureTool = super::MeasureToolRust;
+
+        #[inherit]
+        #[rust_name = "set_flag"]
+        fn setFlag(self: Pin<&mut MeasureTool>, flag: QQuickItemFlag, enabled: bool);
+
+        #[inherit]
+        fn update(self: Pin<&mut MeasureTool>);
+
+        #[inherit]
+        fn size(self: &MeasureTool) -> QSizeF;
+
+        #[inherit]
+        #[rust_name = "update_labels"]
+        unsafe fn updateLabels(
+            self: Pin<&mut MeasureTool>,
+            node: *mut QSGNode,
+            positions: &[QPointF],
+            texts: &[QString],
+        );
+
+        /// Remove all the points
+        #[qinvokable]
+        fn clear(self: Pin<&mut MeasureTool>);
+
+        /// Remove the last placed point
+        #[qinvokable]
+        #[cxx_name = "removeLastPoint"]
+        fn remove_last_point(self: Pin<&mut MeasureTool>);
+
+        #[cxx_override]
+        #[cxx_name = "pointClicked"]
+        fn point_clicked(
+            self: Pin<&mut MeasureTool>,
+            position: QPointF,
+            hit: bool,
+            scene_position: QVector3D,
+        );
+
+        #[cxx_override]
+        #[cxx_name = "updatePaintNode"]
+        unsafe fn update_paint_node(
+            self: Pin<&mut MeasureTool>,
+            old_node: *mut QSGNode,
+            update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+        ) -> *mut QSGNode;
+    }
+}
+
+const LINE_WIDTH: f32 = 2.;
+/// Size in pixels of the squares drawn on the points
+const POINT_SIZE: f32 = 6.;
+
+pub struct MeasureToolRust {
+    camera_position: QVector3D,
+    /// Rotation of the camera, as a quaternion (x, y, z, w)
+    camera_rotation: QVector4D,
+    /// Vertical field of view of the camera, in degrees
+    camera_vertical_fov: f32,
+    camera_near_plane: f32,
+    camera_far_plane: f32,
+    /// Color of the dimension lines
+    line_color: QColor,
+    /// Unit appended to the distances in labels
+    unit: QString,
+    /// Whether the last point is linked back to the first one
+    closed: bool,
+
+    point_count: i32,
+    /// Length of the lines between the points, in scene units
+    distance: f32,
+    /// Angle at the point before the last one, in degrees
+    angle: f32,
+    /// Area of the polygon formed by the points, in square scene units
+    area: f32,
+
+    /// World positions of the points
+    points: Vec<glam::Vec3>,
+}
+
+impl Default for MeasureToolRust {
+    fn default() -> Self {
+        Self {
+            camera_position: QVector3D::default(),
+            camera_rotation: QVector4D::new(0., 0., 0., 1.),
+            camera_vertical_fov: 60.,
+            camera_near_plane: 10.,
+            camera_far_plane: 10000.,
+            line_color: QColor::from_rgb(255, 214, 0),
+            unit: QString::default(),
+            closed: false,
+            point_count: 0,
+            distance: 0.,
+            angle: 0.,
+            area: 0.,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl MeasureToolRust {
+    fn camera(&self) -> Camera {
+        Camera::new(
+            &self.camera_position,
+            &self.camera_rotation,
+            self.camera_vertical_fov,
+            self.camera_near_plane,
+            self.camera_far_plane,
+        )
+    }
+
+    /// `value` followed by `unit` raised to `power`
+    fn format(&self, value: f32, power: &str) -> QString {
+        let unit = self.unit.to_string();
+        if unit.is_empty() {
+            QString::from(&format!("{value:.2}"))
+        } else {
+            QString::from(&format!("{value:.2} {unit}{power}"))
+        }
+    }
+}
+
+impl cxx_qt::Initialize for ffi::MeasureTool {
+    fn initialize(mut self: Pin<&mut Self>) {
+        self.as_mut()
+            .set_flag(QQuickItemFlag::ItemHasContents, true);
+
+        self.as_mut()
+            .on_camera_position_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_rotation_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_vertical_fov_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_near_plane_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_camera_far_plane_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_line_color_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_unit_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_closed_changed(|qobject| qobject.update_measures())
+            .release();
+    }
+}
+
+impl ffi::MeasureTool {
+    fn clear(mut self: Pin<&mut Self>) {
+        self.as_mut().rust_mut().points.clear();
+        self.update_measures();
+    }
+
+    fn remove_last_point(mut self: Pin<&mut Self>) {
+        self.as_mut().rust_mut().points.pop();
+        self.update_measures();
+    }
+
+    fn point_clicked(
+        mut self: Pin<&mut Self>,
+        position: QPointF,
+        hit: bool,
+        scene_position: QVector3D,
+    ) {
+        let point = if hit {
+            Some(glam::Vec3::new(
+                scene_position.x(),
+                scene_position.y(),
+                scene_position.z(),
+            ))
+        } else {
+            let size = self.size();
+            let (origin, direction) = self.rust().camera().screen_ray(
+                glam::Vec2::new(position.x() as f32, position.y() as f32),
+                size.width() as f32,
+                size.height() as f32,
+            );
+            ground_intersection(origin, direction)
+        };
+
+        if let Some(point) = point {
+            self.as_mut().rust_mut().points.push(point);
+            self.update_measures();
+        }
+    }
+
+    fn update_measures(mut self: Pin<&mut Self>) {
+        let (point_count, distance, angle, area) = {
+            let this = self.rust();
+            (
+                this.points.len() as i32,
+                segments(&this.points, this.closed)
+                    .map(|(a, b)| a.distance(b))
+                    .sum::<f32>(),
+                last_angle(&this.points).unwrap_or(0.),
+                polygon_area(&this.points),
+            )
+        };
+
+        if self.rust().point_count != point_count {
+            self.as_mut().rust_mut().point_count = point_count;
+            self.as_mut().point_count_changed();
+        }
+        if self.rust().distance != distance {
+            self.as_mut().rust_mut().distance = distance;
+            self.as_mut().distance_changed();
+        }
+        if self.rust().angle != angle {
+            self.as_mut().rust_mut().angle = angle;
+            self.as_mut().angle_changed();
+        }
+        if self.rust().area != area {
+            self.as_mut().rust_mut().area = area;
+            self.as_mut().area_changed();
+        }
+        self.update();
+    }
+
+    unsafe fn update_paint_node(
+        mut self: Pin<&mut Self>,
+        old_node: *mut QSGNode,
+        _update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+    ) -> *mut QSGNode {
+        let size = self.size();
+        let size = glam::Vec2::new(size.width() as f32, size.height() as f32);
+        let this = self.rust();
+        let camera = this.camera();
+        let color = overlay::rgba(&this.line_color);
+        let to_screen = |point: glam::Vec3| camera.world_to_screen(point, size.x, size.y);
+
+        let mut mesh = OverlayMesh::default();
+        let mut positions = Vec::new();
+        let mut texts = Vec::new();
+        if size.min_element() > 0. {
+            for (start, end) in segments(&this.points, this.closed) {
+                overlay::world_line(
+                    &mut mesh,
+                    &camera,
+                    size,
+                    (start, end),
+                    LINE_WIDTH,
+                    (color, color),
+                );
+                if let Some(middle) = to_screen(start.lerp(end, 0.5)) {
+                    positions.push(middle);
+                    texts.push(this.format(start.distance(end), ""));
+                }
+            }
+
+            for point in this.points.iter().filter_map(|point| to_screen(*point)) {
+                let half = glam::Vec2::splat(POINT_SIZE / 2.);
+                let corners = [
+                    point - half,
+                    point + glam::Vec2::new(half.x, -half.y),
+                    point + half,
+                    point + glam::Vec2::new(-half.x, half.y),
+                ];
+                mesh.quad(corners, [color; 4]);
+            }
+
+            let vertex = this.points.len().checked_sub(2).map(|i| this.points[i]);
+            if let Some((angle, position)) =
+                last_angle(&this.points).zip(vertex.and_then(to_screen))
+            {
+                positions.push(position);
+                texts.push(QString::from(&format!("{angle:.1}°")));
+            }
+
+            if this.closed && this.points.len() > 2 {
+                let centroid = this.points.iter().sum::<glam::Vec3>() / this.points.len() as f32;
+                if let Some(position) = to_screen(centroid) {
+                    positions.push(position);
+                    texts.push(this.format(this.area, "²"));
+                }
+            }
+        }
+        let positions: Vec<QPointF> = positions
+            .iter()
+            .map(|position| QPointF::new(position.x as f64, position.y as f64))
+            .collect();
+
+        unsafe {
+            let node = crate::gizmo::ffi::gizmo_update_paint_node(
+                old_node,
+                &mesh.vertices,
+                &mesh.rgba8_colors(),
+                &mesh.indices,
+            );
+            self.as_mut().update_labels(node, &positions, &texts);
+            node
+        }
+    }
+}
+
+/// Consecutive `points` linked by a dimension line, the last one linked back to the first one
+/// when `closed`
+fn segments(
+    points: &[glam::Vec3],
+    closed: bool,
+) -> impl Iterator<Item = (glam::Vec3, glam::Vec3)> + '_ {
+    let closing = (closed && points.len() > 2).then(|| (*points.last().unwrap(), points[0]));
+    points
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .chain(closing)
+}
+
+/// Intersection of a ray with the ground plane
+fn ground_intersection(origin: glam::Vec3, direction: glam::Vec3) -> Option<glam::Vec3> {
+    let t = -origin.y / direction.y;
+    (t.is_finite() && t > 0.).then(|| origin + direction * t)
+}
+
+/// Angle in degrees at the point before the last one, between the lines to its neighbours
+fn last_angle(points: &[glam::Vec3]) -> Option<f32> {
+    let [before, vertex, after] = points.get(points.len().checked_sub(3)?..)? else {
+        return None;
+    };
+    let angle = (*before - *vertex).angle_between(*after - *vertex);
+    angle.is_finite().then(|| angle.to_degrees())
+}
+
+/// Area of the polygon formed by `points`, assumed planar
+fn polygon_area(points: &[glam::Vec3]) -> f32 {
+    if points.len() < 3 {
+        return 0.;
+    }
+
+    // Newell's method, the sum of the cross products is twice the area times the polygon normal
+    let origin = points[0];
+    let normal: glam::Vec3 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| (*a - origin).cross(*b - origin))
+        .sum();
+    normal.length() / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    /// Unit square on the ground
+    const SQUARE: [glam::Vec3; 4] = [
+        glam::Vec3::new(0., 0., 0.),
+        glam::Vec3::new(1., 0., 0.),
+        glam::Vec3::new(1., 0., 1.),
+        glam::Vec3::new(0., 0., 1.),
+    ];
+
+    #[test]
+    fn open_segments_link_consecutive_points() {
+        assert_eq!(segments(&SQUARE, false).count(), 3);
+        assert_eq!(segments(&SQUARE[..1], false).count(), 0);
+    }
+
+    #[test]
+    fn closed_segments_link_the_last_point_to_the_first() {
+        assert_eq!(segments(&SQUARE, true).last(), Some((SQUARE[3], SQUARE[0])));
+        // Two points make a single line, whether closed or not
+        assert_eq!(segments(&SQUARE[..2], true).count(), 1);
+    }
+
+    #[test]
+    fn ray_down_meets_the_ground() {
+        let point = ground_intersection(glam::Vec3::new(0., 10., 0.), glam::Vec3::new(1., -1., 0.));
+        assert_eq!(point, Some(glam::Vec3::new(10., 0., 0.)));
+    }
+
+    #[test]
+    fn ray_parallel_to_or_away_from_the_ground_misses_it() {
+        let origin = glam::Vec3::new(0., 10., 0.);
+        assert_eq!(ground_intersection(origin, glam::Vec3::X), None);
+        assert_eq!(ground_intersection(origin, glam::Vec3::Y), None);
+    }
+
+    #[test]
+    fn angle_is_measured_at_the_point_before_the_last() {
+        let angle = last_angle(&SQUARE).unwrap();
+        assert!((angle - 90.).abs() < EPSILON, "{angle}");
+
+        let angle = last_angle(&[SQUARE[1], SQUARE[0], SQUARE[2]]).unwrap();
+        assert!((angle - 45.).abs() < EPSILON, "{angle}");
+    }
+
+    #[test]
+    fn angle_needs_three_distinct_points() {
+        assert_eq!(last_angle(&SQUARE[..2]), None);
+        assert_eq!(last_angle(&[SQUARE[0], SQUARE[0], SQUARE[1]]), None);
+    }
+
+    #[test]
+    fn area_is_the_polygon_area() {
+        assert!((polygon_area(&SQUARE) - 1.).abs() < EPSILON);
+        assert!((polygon_area(&SQUARE[..3]) - 0.5).abs() < EPSILON);
+        assert_eq!(polygon_area(&SQUARE[..2]), 0.);
+    }
+
+    #[test]
+    fn area_of_a_concave_tilted_polygon() {
+        // L shape of area 3, on a plane tilted around the X axis
+        let rotation = glam::Quat::from_rotation_x(30f32.to_radians());
+        let points = [(0., 0.), (2., 0.), (2., 1.), (1., 1.), (1., 2.), (0., 2.)]
+            .map(|(x, z)| rotation * glam::Vec3::new(x, 0., z));
+
+        let area = polygon_area(&points);
+        assert!((area - 3.).abs() < EPSILON, "{area}");
+    }
+}
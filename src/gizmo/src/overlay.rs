@@ -0,0 +1,44 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Triangles drawn by the items over a QtQuick3D view, in the layout expected by
+//! `gizmo_update_paint_node`.
+
+use cxx_qt_lib::QColor;
+pub use gizmo_overlay::{Label, OverlayFrame, OverlayMesh};
+
+use crate::camera::Camera;
+
+/// Line of `mesh` between the world positions `start` and `end` seen by `camera` in an item of
+/// `size`, cut by the near plane
+pub fn world_line(
+    mesh: &mut OverlayMesh,
+    camera: &Camera,
+    size: glam::Vec2,
+    (start, end): (glam::Vec3, glam::Vec3),
+    width: f32,
+    (start_color, end_color): ([f32; 4], [f32; 4]),
+) {
+    let Some((from, to)) = camera.clip_segment(start, end) else {
+        return;
+    };
+    let point = |t: f32| camera.world_to_screen(start.lerp(end, t), size.x, size.y);
+    let color = |t: f32| {
+        glam::Vec4::from(start_color)
+            .lerp(glam::Vec4::from(end_color), t)
+            .to_array()
+    };
+    if let (Some(start), Some(end)) = (point(from), point(to)) {
+        mesh.line(start, end, width, color(from), color(to));
+    }
+}
+
+/// Components of `color` between 0 and 1
+pub fn rgba(color: &QColor) -> [f32; 4] {
+    [
+        color.red_f(),
+        color.green_f(),
+        color.blue_f(),
+        color.alpha_f(),
+    ]
+}
@@ -0,0 +1,429 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Cube showing the orientation of the camera, clicking one of its faces, edges or corners
+//! requests the camera to look at it.
+//!
+//! ```qml
+//! ViewCube {
+//!     width: 120
+//!     height: 120
+//!     cameraRotation: camera.rotation.toVector4d()
+//!     animationDuration: 300
+//!     onOrientationRequested: rotation => camera.rotation = rotation
+//! }
+//! ```
+
+use core::pin::Pin;
+
+use cxx_qt::CxxQtType;
+use cxx_qt_lib::{QColor, QPointF, QVector4D};
+use ffi::{QQuickItemFlag, QQuickItemUpdatePaintNodeData, QSGNode};
+
+use crate::overlay::{self, OverlayMesh};
+
+#[cxx_qt::bridge]
+pub mod ffi {
+    // Shared with the gizmo, whose scene graph rendering is reused
+    #[namespace = "rust::cxxqtlib1"]
+    unsafe extern "C++" {
+        include!("cxxqtlib1_qquickitem.h");
+        type QQuickItemFlag = crate::gizmo::ffi::QQuickItemFlag;
+        type QQuickItemUpdatePaintNodeData = crate::gizmo::ffi::QQuickItemUpdatePaintNodeData;
+    }
+
+    unsafe extern "C++" {
+        include!(<QtQuick/QSGNode>);
+        type QSGNode = crate::gizmo::ffi::QSGNode;
+    }
+
+    unsafe extern "C++" {
+        include!("cxx-qt-lib/qpointf.h");
+        type QPointF = cxx_qt_lib::QPointF;
+
+        include!("cxx-qt-lib/qsizef.h");
+        type QSizeF = cxx_qt_lib::QSizeF;
+
+        include!("cxx-qt-lib/qvector4d.h");
+        type QVector4D = cxx_qt_lib::QVector4D;
+
+        include!("cxx-qt-lib/qcolor.h");
+        type QColor = cxx_qt_lib::QColor;
+    }
+
+    unsafe extern "C++" {
+        include!("view_cube.h");
+        type ViewCubeInteractionItem;
+    }
+
+    unsafe extern "RustQt" {
+        #[qobject]
+        #[qml_element]
+        #[base = ViewCubeInteractionItem]
+        #[qproperty(QVector4D, cameraRotation, rust_name = "camera_rotation")]
+        #[qproperty(QColor, faceColor, rust_name = "face_color")]
+        #[qproperty(QColor, edgeColor, rust_name = "edge_color")]
+        #[qproperty(QColor, hoverColor, rust_name = "hover_color")]
+        type ViewCube = super::ViewCubeRust;
+
+        #[inherit]
+        #[rust_name = "set_flag"]
+        fn setFlag(self: Pin<&mut ViewCube>, flag: QQuickItemFlag, enabled: bool);
+
+        #[inherit]
+        fn update(self: Pin<&mut ViewCube>);
+
+        #[inherit]
+        fn size(self: &ViewCube) -> QSizeF;
+
+        #[inherit]
+        #[rust_name = "request_orientation"]
+        fn requestOrientation(self: Pin<&mut ViewCube>, from: QVector4D, to: QVector4D);
+
+        #[cxx_override]
+        fn hover(self: Pin<&mut ViewCube>, position: QPointF) -> bool;
+
+        #[cxx_override]
+        #[cxx_name = "clearHover"]
+        fn clear_hover(self: Pin<&mut ViewCube>);
+
+        #[cxx_override]
+        fn activate(self: Pin<&mut ViewCube>);
+
+        #[cxx_override]
+        #[cxx_name = "updatePaintNode"]
+        unsafe fn update_paint_node(
+            self: Pin<&mut ViewCube>,
+            old_node: *mut QSGNode,
+            update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+        ) -> *mut QSGNode;
+    }
+}
+
+/// Fraction of the cube width taken by the edges and corners
+const EDGE_SIZE: f32 = 0.2;
+
+pub struct ViewCubeRust {
+    /// Rotation of the camera, as a quaternion (x, y, z, w)
+    camera_rotation: QVector4D,
+    /// Color of the faces
+    face_color: QColor,
+    /// Color of the edges and corners
+    edge_color: QColor,
+    /// Color of the part under the mouse
+    hover_color: QColor,
+
+    /// Part of the cube under the mouse
+    hovered: Option<glam::IVec3>,
+}
+
+impl Default for ViewCubeRust {
+    fn default() -> Self {
+        Self {
+            camera_rotation: QVector4D::new(0., 0., 0., 1.),
+            face_color: QColor::from_rgb(207, 216, 220),
+            edge_color: QColor::from_rgb(144, 164, 174),
+            hover_color: QColor::from_rgb(0, 125, 255),
+            hovered: None,
+        }
+    }
+}
+
+impl ViewCubeRust {
+    fn camera_rotation(&self) -> glam::Quat {
+        let rotation = glam::Quat::from_xyzw(
+            self.camera_rotation.x(),
+            self.camera_rotation.y(),
+            self.camera_rotation.z(),
+            self.camera_rotation.w(),
+        );
+        if rotation.length_squared() > 0. {
+            rotation.normalize()
+        } else {
+            glam::Quat::IDENTITY
+        }
+    }
+}
+
+impl cxx_qt::Initialize for ffi::ViewCube {
+    fn initialize(mut self: Pin<&mut Self>) {
+        self.as_mut()
+            .set_flag(QQuickItemFlag::ItemHasContents, true);
+
+        self.as_mut()
+            .on_camera_rotation_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_face_color_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_edge_color_changed(|qobject| qobject.update())
+            .release();
+        self.as_mut()
+            .on_hover_color_changed(|qobject| qobject.update())
+            .release();
+    }
+}
+
+impl ffi::ViewCube {
+    fn cells(&self) -> Vec<Cell> {
+        let size = self.size();
+        cells(
+            self.rust().camera_rotation(),
+            glam::Vec2::new(size.width() as f32, size.height() as f32),
+        )
+    }
+
+    fn set_hovered(mut self: Pin<&mut Self>, hovered: Option<glam::IVec3>) {
+        if self.rust().hovered != hovered {
+            self.as_mut().rust_mut().hovered = hovered;
+            self.update();
+        }
+    }
+
+    fn hover(self: Pin<&mut Self>, position: QPointF) -> bool {
+        let position = glam::Vec2::new(position.x() as f32, position.y() as f32);
+        let hovered = self
+            .cells()
+            .into_iter()
+            .find(|cell| cell.contains(position))
+            .map(|cell| cell.part);
+        self.set_hovered(hovered);
+        hovered.is_some()
+    }
+
+    fn clear_hover(self: Pin<&mut Self>) {
+        self.set_hovered(None);
+    }
+
+    fn activate(self: Pin<&mut Self>) {
+        let Some(part) = self.rust().hovered else {
+            return;
+        };
+
+        let rotation = orientation(part);
+        let from = self.rust().camera_rotation;
+        self.request_orientation(
+            from,
+            QVector4D::new(rotation.x, rotation.y, rotation.z, rotation.w),
+        );
+    }
+
+    unsafe fn update_paint_node(
+        self: Pin<&mut Self>,
+        old_node: *mut QSGNode,
+        _update_paint_node_data: *mut QQuickItemUpdatePaintNodeData,
+    ) -> *mut QSGNode {
+        let this = self.rust();
+        let mut mesh = OverlayMesh::default();
+        for cell in self.cells() {
+            let [red, green, blue, alpha] = if this.hovered == Some(cell.part) {
+                overlay::rgba(&this.hover_color)
+            } else if cell.is_face() {
+                overlay::rgba(&this.face_color)
+            } else {
+                overlay::rgba(&this.edge_color)
+            };
+            // Darken the faces turned away from the camera
+            let shade = 0.6 + 0.4 * cell.facing;
+            let color = [red * shade, green * shade, blue * shade, alpha];
+
+            mesh.quad(cell.corners, [color; 4]);
+        }
+
+        unsafe {
+            crate::gizmo::ffi::gizmo_update_paint_node(
+                old_node,
+                &mesh.vertices,
+                &mesh.rgba8_colors(),
+                &mesh.indices,
+            )
+        }
+    }
+}
+
+/// Visible part of a face of the cube
+struct Cell {
+    /// Direction from the cube center through the part, with components in -1, 0 and 1: one
+    /// non-zero component for a face, two for an edge and three for a corner
+    part: glam::IVec3,
+    /// Quad of the part in item coordinates
+    corners: [glam::Vec2; 4],
+    /// Cosine of the angle between the face normal and the view direction
+    facing: f32,
+}
+
+impl Cell {
+    fn is_face(&self) -> bool {
+        self.part.abs().element_sum() == 1
+    }
+
+    fn contains(&self, point: glam::Vec2) -> bool {
+        let sides: Vec<f32> = (0..4)
+            .map(|i| {
+                let start = self.corners[i];
+                let end = self.corners[(i + 1) % 4];
+                (end - start).perp_dot(point - start)
+            })
+            .collect();
+        // The quad winding depends on the camera
+        sides.iter().all(|side| *side >= 0.) || sides.iter().all(|side| *side <= 0.)
+    }
+}
+
+/// Visible cells of the cube seen with the camera `rotation`, centered in an item of `size`
+fn cells(rotation: glam::Quat, size: glam::Vec2) -> Vec<Cell> {
+    let inverse = rotation.inverse();
+    // The cube fits in the item whatever its orientation
+    let scale = size.min_element() / 2. / 3f32.sqrt();
+    let to_item = |point: glam::Vec3| {
+        let point = inverse * point;
+        size / 2. + glam::Vec2::new(point.x, -point.y) * scale
+    };
+    let bounds = [-1., -1. + 2. * EDGE_SIZE, 1. - 2. * EDGE_SIZE, 1.];
+
+    let mut cells = Vec::new();
+    for axis in 0..3 {
+        for sign in [-1, 1] {
+            let mut normal = glam::IVec3::ZERO;
+            normal[axis] = sign;
+            let facing = (inverse * normal.as_vec3()).z;
+            if facing <= 0. {
+                continue;
+            }
+
+            let u = glam::IVec3::AXES[(axis + 1) % 3];
+            let v = glam::IVec3::AXES[(axis + 2) % 3];
+            let corner = |i: usize, j: usize| {
+                to_item(normal.as_vec3() + u.as_vec3() * bounds[i] + v.as_vec3() * bounds[j])
+            };
+            for i in 0..3 {
+                for j in 0..3 {
+                    cells.push(Cell {
+                        part: normal + u * (i as i32 - 1) + v * (j as i32 - 1),
+                        corners: [
+                            corner(i, j),
+                            corner(i + 1, j),
+                            corner(i + 1, j + 1),
+                            corner(i, j + 1),
+                        ],
+                        facing,
+                    });
+                }
+            }
+        }
+    }
+    cells
+}
+
+/// Camera rotation looking at `part` of the cube
+fn orientation(part: glam::IVec3) -> glam::Quat {
+    let back = part.as_vec3().normalize();
+    // Top and bottom views are the front view tilted, its face at the bottom of the top view and at
+    // the top of the bottom view
+    let up = if part.x == 0 && part.z == 0 {
+        glam::Vec3::Z * -part.y as f32
+    } else {
+        glam::Vec3::Y
+    };
+    let right = up.cross(back).normalize();
+
+    glam::Quat::from_mat3(&glam::Mat3::from_cols(right, back.cross(right), back))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+    const SIZE: glam::Vec2 = glam::Vec2::new(120., 120.);
+
+    /// Every face, edge and corner of the cube
+    fn parts() -> impl Iterator<Item = glam::IVec3> {
+        (-1..=1)
+            .flat_map(|x| {
+                (-1..=1).flat_map(move |y| (-1..=1).map(move |z| glam::IVec3::new(x, y, z)))
+            })
+            .filter(|part| *part != glam::IVec3::ZERO)
+    }
+
+    fn hit(rotation: glam::Quat, point: glam::Vec2) -> Option<glam::IVec3> {
+        cells(rotation, SIZE)
+            .into_iter()
+            .find(|cell| cell.contains(point))
+            .map(|cell| cell.part)
+    }
+
+    #[test]
+    fn camera_looking_at_a_face_sees_only_that_face() {
+        let cells = cells(glam::Quat::IDENTITY, SIZE);
+
+        assert_eq!(cells.len(), 9);
+        assert!(cells.iter().all(|cell| cell.part.z == 1));
+        assert_eq!(
+            cells.iter().filter(|cell| cell.is_face()).count(),
+            1,
+            "only the center cell is the face"
+        );
+    }
+
+    #[test]
+    fn item_center_hits_the_face_the_camera_looks_at() {
+        assert_eq!(hit(glam::Quat::IDENTITY, SIZE / 2.), Some(glam::IVec3::Z));
+        assert_eq!(hit(glam::Quat::IDENTITY, glam::Vec2::ZERO), None);
+    }
+
+    #[test]
+    fn edges_and_corners_surround_the_face() {
+        let quarter = SIZE.x / 2. / 3f32.sqrt() * (1. - EDGE_SIZE);
+        let center = SIZE / 2.;
+
+        // The item y axis goes down
+        assert_eq!(
+            hit(glam::Quat::IDENTITY, center + glam::Vec2::new(quarter, 0.)),
+            Some(glam::IVec3::new(1, 0, 1))
+        );
+        assert_eq!(
+            hit(glam::Quat::IDENTITY, center + glam::Vec2::new(0., quarter)),
+            Some(glam::IVec3::new(0, -1, 1))
+        );
+        assert_eq!(
+            hit(
+                glam::Quat::IDENTITY,
+                center + glam::Vec2::new(-quarter, -quarter)
+            ),
+            Some(glam::IVec3::new(-1, 1, 1))
+        );
+    }
+
+    #[test]
+    fn camera_oriented_on_a_part_looks_at_it() {
+        for part in parts() {
+            let rotation = orientation(part);
+
+            assert!(rotation.is_normalized(), "{part}");
+            // The camera looks along its -Z axis, from the part toward the cube center
+            let back = rotation * glam::Vec3::Z;
+            assert!(
+                back.abs_diff_eq(part.as_vec3().normalize(), EPSILON),
+                "{part}: {back}"
+            );
+            assert_eq!(hit(rotation, SIZE / 2.), Some(part));
+        }
+    }
+
+    #[test]
+    fn top_and_bottom_views_are_the_front_view_tilted() {
+        let above = SIZE / 2. - glam::Vec2::new(0., SIZE.y / 4.);
+        let below = SIZE / 2. + glam::Vec2::new(0., SIZE.y / 4.);
+
+        assert_eq!(
+            hit(orientation(glam::IVec3::Y), below),
+            Some(glam::IVec3::new(0, 1, 1))
+        );
+        assert_eq!(
+            hit(orientation(glam::IVec3::NEG_Y), above),
+            Some(glam::IVec3::new(0, -1, 1))
+        );
+    }
+}
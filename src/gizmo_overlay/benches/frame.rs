@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Per-frame cost of the gizmo for 1, 10 and 1000 targets.
+//!
+//! ```bash
+//! cargo bench -p gizmo_overlay --bench frame
+//! ```
+//!
+//! Qt isn't involved: a frame is the `transform_gizmo` update and draw, then the `OverlayFrame`
+//! built by `Gizmo::update_paint_node`, compared with the previous one and its colors converted
+//! for the scene graph when it changed.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use gizmo_overlay::{OverlayFrame, OverlayMesh};
+
+const TARGET_COUNTS: [usize; 3] = [1, 10, 1000];
+const WARM_UP_FRAME_COUNT: u32 = 10;
+const FRAME_COUNT: u32 = 500;
+const WIDTH: f32 = 1280.;
+const HEIGHT: f32 = 720.;
+const PIXEL_RATIO: f32 = 2.;
+/// The view fills the item
+const VIEW_TO_ITEM: [f32; 6] = [1., 0., 0., 1., 0., 0.];
+
+fn main() {
+    for target_count in TARGET_COUNTS {
+        let idle = measure(target_count, |_| transform_gizmo::GizmoInteraction {
+            cursor_pos: (0., 0.),
+            hovered: false,
+            drag_started: false,
+            dragging: false,
+        });
+        // Grab the X arrow and drag it along the screen
+        let drag = measure(target_count, |frame| transform_gizmo::GizmoInteraction {
+            cursor_pos: (
+                (WIDTH / 2. + 40. + frame as f32) * PIXEL_RATIO,
+                HEIGHT / 2. * PIXEL_RATIO,
+            ),
+            hovered: true,
+            drag_started: frame == 0,
+            dragging: true,
+        });
+
+        println!("{target_count:>5} targets: idle {idle:>10.2?}, drag {drag:>10.2?} per frame");
+    }
+}
+
+/// Mean duration of a frame of `target_count` targets, `interaction` giving the interaction of
+/// each frame
+fn measure(
+    target_count: usize,
+    interaction: impl Fn(u32) -> transform_gizmo::GizmoInteraction,
+) -> Duration {
+    let mut gizmo = transform_gizmo::Gizmo::new(config());
+    let mut targets = targets(target_count);
+    let mut previous = OverlayFrame::default();
+
+    for frame in 0..WARM_UP_FRAME_COUNT {
+        run_frame(&mut gizmo, &mut targets, interaction(frame), &mut previous);
+    }
+
+    let start = Instant::now();
+    for frame in 0..FRAME_COUNT {
+        run_frame(
+            &mut gizmo,
+            &mut targets,
+            interaction(WARM_UP_FRAME_COUNT + frame),
+            &mut previous,
+        );
+    }
+    start.elapsed() / FRAME_COUNT
+}
+
+fn run_frame(
+    gizmo: &mut transform_gizmo::Gizmo,
+    targets: &mut Vec<transform_gizmo::math::Transform>,
+    interaction: transform_gizmo::GizmoInteraction,
+    previous: &mut OverlayFrame,
+) {
+    if let Some((_, transforms)) = gizmo.update(interaction, targets) {
+        *targets = transforms;
+    }
+
+    let draw_data = gizmo.draw();
+    let frame = OverlayFrame::new(
+        OverlayMesh::default(),
+        Vec::new(),
+        &draw_data,
+        PIXEL_RATIO,
+        VIEW_TO_ITEM,
+    );
+    // An identical frame isn't uploaded
+    if *previous != frame {
+        black_box(frame.mesh.rgba8_colors());
+        *previous = frame;
+    }
+}
+
+fn config() -> transform_gizmo::GizmoConfig {
+    let eye = glam::DVec3::new(200., 500., 500.);
+    let view_matrix = glam::DMat4::look_at_rh(eye, glam::DVec3::ZERO, glam::DVec3::Y);
+    let projection_matrix =
+        glam::DMat4::perspective_rh(60f64.to_radians(), (WIDTH / HEIGHT) as f64, 10., 10000.);
+
+    transform_gizmo::GizmoConfig {
+        view_matrix: view_matrix.into(),
+        projection_matrix: projection_matrix.into(),
+        viewport: transform_gizmo::Rect {
+            min: transform_gizmo::math::Pos2 { x: 0., y: 0. },
+            max: transform_gizmo::math::Pos2 {
+                x: WIDTH * PIXEL_RATIO,
+                y: HEIGHT * PIXEL_RATIO,
+            },
+        },
+        modes: transform_gizmo::EnumSet::all(),
+        pixels_per_point: PIXEL_RATIO,
+        ..Default::default()
+    }
+}
+
+/// `count` targets on a grid around the origin
+fn targets(count: usize) -> Vec<transform_gizmo::math::Transform> {
+    let side = (count as f64).sqrt().ceil() as usize;
+    (0..count)
+        .map(|i| {
+            let position = glam::DVec3::new((i % side) as f64, 0., (i / side) as f64) * 20.;
+            transform_gizmo::math::Transform::from_scale_rotation_translation(
+                glam::DVec3::ONE,
+                glam::DQuat::IDENTITY,
+                position,
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: Olivier Le Doeuff <olivier.ldff@gmail.com>
+// SPDX-License-Identifier: MIT
+
+//! Triangles and labels of the `gizmo` overlays, built without Qt so that `benches/frame.rs`
+//! measures the code run on every frame.
+
+#[derive(Default, PartialEq)]
+pub struct OverlayMesh {
+    pub vertices: Vec<[f32; 2]>,
+    pub colors: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+impl OverlayMesh {
+    /// Line of `width` pixels from `start` to `end`, its color going from `start_color` to
+    /// `end_color`
+    pub fn line(
+        &mut self,
+        start: glam::Vec2,
+        end: glam::Vec2,
+        width: f32,
+        start_color: [f32; 4],
+        end_color: [f32; 4],
+    ) {
+        let normal = (end - start).normalize_or_zero().perp() * width / 2.;
+        if normal == glam::Vec2::ZERO {
+            return;
+        }
+
+        self.quad(
+            [start + normal, start - normal, end - normal, end + normal],
+            [start_color, start_color, end_color, end_color],
+        );
+    }
+
+    /// Triangles joining `center` to each pair of consecutive `rim` points
+    pub fn fan(&mut self, center: glam::Vec2, rim: &[glam::Vec2], color: [f32; 4]) {
+        let first = self.vertices.len() as u32;
+        self.vertices.push(center.to_array());
+        self.vertices
+            .extend(rim.iter().map(|point| point.to_array()));
+        self.colors
+            .extend(core::iter::repeat_n(color, rim.len() + 1));
+        self.indices.extend(
+            (1..rim.len() as u32).flat_map(|index| [first, first + index, first + index + 1]),
+        );
+    }
+
+    pub fn quad(&mut self, corners: [glam::Vec2; 4], colors: [[f32; 4]; 4]) {
+        let first = self.vertices.len() as u32;
+        self.vertices
+            .extend(corners.map(|corner| corner.to_array()));
+        self.colors.extend(colors);
+        self.indices
+            .extend([0, 1, 2, 0, 2, 3].map(|index| first + index));
+    }
+
+    /// Append the triangles of `draw_data`, drawn by `transform_gizmo` in physical pixels for
+    /// `pixel_ratio`
+    pub fn append_gizmo(&mut self, draw_data: &transform_gizmo::GizmoDrawData, pixel_ratio: f32) {
+        let first = self.vertices.len() as u32;
+        self.vertices.extend(
+            draw_data
+                .vertices
+                .iter()
+                .map(|[x, y]| [x / pixel_ratio, y / pixel_ratio]),
+        );
+        self.colors.extend_from_slice(&draw_data.colors);
+        self.indices
+            .extend(draw_data.indices.iter().map(|index| first + index));
+    }
+
+    /// Colors as the 8 bit components uploaded to the scene graph
+    pub fn rgba8_colors(&self) -> Vec<[u8; 4]> {
+        self.colors
+            .iter()
+            .map(|color| color.map(|component| (component.clamp(0., 1.) * 255. + 0.5) as u8))
+            .collect()
+    }
+}
+
+#[derive(PartialEq)]
+pub struct Label {
+    /// Center of the label, in view coordinates until mapped to the item
+    pub position: glam::Vec2,
+    pub text: String,
+}
+
+/// Content of the gizmo overlay node, kept to skip uploading a frame identical to the previous one
+#[derive(Default, PartialEq)]
+pub struct OverlayFrame {
+    /// Triangles in item coordinates
+    pub mesh: OverlayMesh,
+    /// Labels centered in item coordinates
+    pub labels: Vec<Label>,
+    /// Labels are rasterized for this ratio
+    pub pixel_ratio: f32,
+}
+
+impl OverlayFrame {
+    /// Frame of the gizmo `draw_data`, drawn in physical pixels for `pixel_ratio`, over the drag
+    /// feedback `mesh` and its `labels`, all mapped from the view to the item by the affine
+    /// `view_to_item` transform `[m11, m12, m21, m22, dx, dy]`
+    pub fn new(
+        mut mesh: OverlayMesh,
+        mut labels: Vec<Label>,
+        draw_data: &transform_gizmo::GizmoDrawData,
+        pixel_ratio: f32,
+        view_to_item: [f32; 6],
+    ) -> Self {
+        mesh.append_gizmo(draw_data, pixel_ratio);
+
+        let [m11, m12, m21, m22, dx, dy] = view_to_item;
+        let to_item = |[x, y]: [f32; 2]| [m11 * x + m21 * y + dx, m12 * x + m22 * y + dy];
+        for vertex in &mut mesh.vertices {
+            *vertex = to_item(*vertex);
+        }
+        for label in &mut labels {
+            label.position = to_item(label.position.to_array()).into();
+        }
+
+        Self {
+            mesh,
+            labels,
+            pixel_ratio,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_rounded_and_clamped_to_8_bits() {
+        let mesh = OverlayMesh {
+            colors: vec![[0., 0.5, 1., 1.], [-1., 0.1, 2., f32::NAN]],
+            ..Default::default()
+        };
+        assert_eq!(mesh.rgba8_colors(), [[0, 128, 255, 255], [0, 26, 255, 0]]);
+    }
+
+    #[test]
+    fn gizmo_is_drawn_over_feedback_in_item_coordinates() {
+        let mut feedback = OverlayMesh::default();
+        feedback.quad([glam::Vec2::ZERO; 4], [[1.; 4]; 4]);
+        let labels = vec![Label {
+            position: glam::Vec2::new(10., 20.),
+            text: "1.00".into(),
+        }];
+        let draw_data = transform_gizmo::GizmoDrawData {
+            vertices: vec![[20., 40.], [40., 40.], [20., 60.]],
+            colors: vec![[0.5; 4]; 3],
+            indices: vec![0, 1, 2],
+        };
+        // Scaled by 2 then moved by (5, 7)
+        let frame = OverlayFrame::new(feedback, labels, &draw_data, 2., [2., 0., 0., 2., 5., 7.]);
+
+        assert_eq!(
+            frame.mesh.vertices[4..],
+            [[25., 47.], [45., 47.], [25., 67.]]
+        );
+        assert_eq!(frame.mesh.colors[4..], [[0.5; 4]; 3]);
+        assert_eq!(frame.mesh.indices[6..], [4, 5, 6]);
+        assert_eq!(frame.labels[0].position, glam::Vec2::new(25., 47.));
+        assert_eq!(frame.pixel_ratio, 2.);
+    }
+}